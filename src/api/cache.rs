@@ -0,0 +1,317 @@
+//! # 响应缓存
+//!
+//! 可选的磁盘缓存，按接口名称和规范化后的请求参数保存原始 JSON 响应及获取时间。
+//! 每个接口可以设置不同的有效期，也可以只读缓存、完全不访问网络（离线模式）。
+//!
+//! 缓存文件位于 `<dir>/<接口名称>/<键哈希前两位>/<键哈希>.json`。
+
+use super::endpoint::Endpoint;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 缓存读写模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// 优先读取未过期的缓存，未命中时请求接口并写入缓存
+    #[default]
+    ReadWrite,
+    /// 只读取缓存（忽略有效期），未命中时返回 [`Error::CacheMiss`]，不访问网络
+    Offline,
+    /// 忽略已有缓存，总是请求接口并写入缓存
+    Refresh,
+}
+
+/// 缓存配置
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    dir: PathBuf,
+    mode: CacheMode,
+    default_ttl: Option<Duration>,
+    ttls: HashMap<&'static str, Option<Duration>>,
+}
+
+impl CacheConfig {
+    /// 缓存保存在 `dir` 目录下，默认永不过期
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CacheMode::default(),
+            default_ttl: None,
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// 读写模式
+    pub fn mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 未单独设置有效期的接口使用的有效期，`None` 表示永不过期
+    pub fn default_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// 单个接口的有效期，`endpoint` 为 [`Endpoint::NAME`]，`None` 表示永不过期
+    pub fn ttl(mut self, endpoint: &'static str, ttl: Option<Duration>) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// 缓存目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn ttl_of(&self, endpoint: &str) -> Option<Duration> {
+        self.ttls.get(endpoint).copied().unwrap_or(self.default_ttl)
+    }
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数（包括已过期）
+    pub misses: u64,
+    /// 因过期而未使用的次数
+    pub expired: u64,
+    /// 写入次数
+    pub writes: u64,
+}
+
+/// 缓存文件内容
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 接口名称
+    endpoint: String,
+    /// 规范化后的请求参数
+    key: String,
+    /// 获取时间（Unix 时间戳，秒）
+    fetched_at: u64,
+    /// 原始响应内容
+    body: String,
+}
+
+/// 磁盘响应缓存
+#[derive(Debug)]
+pub struct ResponseCache {
+    config: CacheConfig,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    expired: AtomicU64,
+    writes: AtomicU64,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            expired: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+        }
+    }
+
+    /// 缓存配置
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// 缓存统计
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            expired: self.expired.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 读取缓存的响应内容
+    ///
+    /// 离线模式下未命中返回 [`Error::CacheMiss`]，其他模式下返回 `None`。
+    pub(crate) async fn load<E: Endpoint>(&self, params: &E) -> Result<Option<String>> {
+        if self.config.mode == CacheMode::Refresh {
+            return Ok(None);
+        }
+        let key = cache_key(params);
+        let path = self.path(E::NAME, &key);
+        let entry = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => match serde_json::from_str::<CacheEntry>(&json) {
+                Ok(entry) if entry.key == key => Some(entry),
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "缓存文件损坏");
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        let entry = match entry {
+            Some(entry) if self.config.mode == CacheMode::Offline || self.is_fresh::<E>(&entry) => {
+                entry
+            }
+            Some(_) => {
+                self.expired.fetch_add(1, Ordering::Relaxed);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                if self.config.mode == CacheMode::Offline {
+                    return Err(Error::CacheMiss {
+                        endpoint: E::NAME,
+                        key,
+                    });
+                }
+                return Ok(None);
+            }
+        };
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(endpoint = E::NAME, key = %key, "命中缓存");
+        Ok(Some(entry.body))
+    }
+
+    /// 写入响应内容，写入失败只记录日志
+    pub(crate) async fn store<E: Endpoint>(&self, params: &E, body: &str) {
+        if let Err(e) = self.try_store(params, body).await {
+            tracing::warn!(endpoint = E::NAME, error = %e, "写入缓存失败");
+        }
+    }
+
+    async fn try_store<E: Endpoint>(&self, params: &E, body: &str) -> anyhow::Result<()> {
+        let key = cache_key(params);
+        let path = self.path(E::NAME, &key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let entry = CacheEntry {
+            endpoint: E::NAME.to_string(),
+            key,
+            fetched_at: unix_now(),
+            body: body.to_string(),
+        };
+        // 先写临时文件再重命名，避免中断时留下不完整的缓存文件
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(&entry)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn is_fresh<E: Endpoint>(&self, entry: &CacheEntry) -> bool {
+        match self.config.ttl_of(E::NAME) {
+            Some(ttl) => unix_now().saturating_sub(entry.fetched_at) < ttl.as_secs(),
+            None => true,
+        }
+    }
+
+    fn path(&self, endpoint: &str, key: &str) -> PathBuf {
+        let hash = hex::encode(Sha256::digest(key.as_bytes()));
+        self.config
+            .dir
+            .join(endpoint)
+            .join(&hash[..2])
+            .join(format!("{}.json", hash))
+    }
+}
+
+/// 规范化请求参数：序列化为键按字典序排列的 JSON
+pub(crate) fn cache_key<E: Endpoint>(params: &E) -> String {
+    serde_json::to_value(params)
+        .expect("请求参数无法序列化")
+        .to_string()
+}
+
+/// 当前 Unix 时间戳（秒）
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Cli, DetailsQueryParams, RetryPolicy};
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const DETAILS_BODY: &str = r#"{"ethnic_minorities_writing":"","gdm":{"type":"Point","coordinates":[[113.9,34.7]]},"id":"abc","place_code":"1","place_meaning":"","place_origin":"","place_type":"农村居民点","place_type_code":"22200","province_name":"河南省","roman_alphabet_spelling":"","standard_name":"唐庄村","province":"410000000000"}"#;
+
+    fn cache_cli(server: &MockServer, config: CacheConfig) -> Cli {
+        Cli::builder()
+            .base_url(format!("{}/9095", server.uri()))
+            .retry_policy(RetryPolicy::none())
+            .cache(config)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_is_normalized() {
+        let key = cache_key(&DetailsQueryParams::new("abc".to_string()));
+        assert_eq!(key, r#"{"id":"abc"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_skips_network() {
+        let server = MockServer::start().await;
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(DETAILS_BODY))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let cli = cache_cli(&server, CacheConfig::new(dir.path()));
+        assert_eq!(cli.details("abc").await.unwrap().standard_name, "唐庄村");
+        assert_eq!(cli.details("abc").await.unwrap().standard_name, "唐庄村");
+        let stats = cli.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.writes), (1, 1, 1));
+
+        // 离线模式读取同一目录
+        let offline = cache_cli(
+            &server,
+            CacheConfig::new(dir.path()).mode(CacheMode::Offline),
+        );
+        assert!(offline.details("abc").await.is_ok());
+        let miss = offline.details("other").await;
+        assert!(matches!(
+            miss,
+            Err(Error::CacheMiss {
+                endpoint: "details",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_refetched() {
+        let server = MockServer::start().await;
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(DETAILS_BODY))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let config = CacheConfig::new(dir.path()).ttl("details", Some(Duration::ZERO));
+        let cli = cache_cli(&server, config);
+        cli.details("abc").await.unwrap();
+        cli.details("abc").await.unwrap();
+        let stats = cli.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.expired, stats.writes), (0, 1, 2));
+    }
+}
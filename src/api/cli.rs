@@ -1,8 +1,30 @@
-use super::{details::*, division::*, search::*};
-use reqwest::{Client, Error};
-
+use super::{
+    cache::*, details::*, division::*, endpoint::*, journal::*, pagination::*, rate_limit::*,
+    retry::*, search::*,
+};
+use crate::error::{Error, Result};
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use reqwest::{Client, Proxy, Url};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 民政部地名服务根地址
+pub const DEFAULT_BASE_URL: &str = "https://dmfw.mca.gov.cn/9095";
+
+/// 地名服务客户端
+///
+/// 克隆开销很小，克隆出的客户端共享连接池、Cookie 和限速器，可以在多个任务间共享。
+#[derive(Clone)]
 pub struct Cli {
     client: Client,
+    base_url: String,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
+    cache: Option<Arc<ResponseCache>>,
+    journal: Option<Arc<Journal>>,
 }
 
 impl Default for Cli {
@@ -13,88 +35,649 @@ impl Default for Cli {
 
 impl Cli {
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
+        Self::builder().build().expect("默认客户端配置无效")
+    }
+
+    /// 创建客户端构建器
+    pub fn builder() -> CliBuilder {
+        CliBuilder::default()
+    }
+
+    /// 服务根地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 拼接接口完整地址
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// 重试策略
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// 限速器
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// 响应缓存（未启用时为 `None`）
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    /// 缓存统计（未启用缓存时为 `None`）
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// 请求日志（未启用时为 `None`）
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_deref()
+    }
+
+    /// 发送接口请求并解析响应
+    ///
+    /// 所有接口调用都经过此方法，统一处理缓存、限速、重试、状态码检查、响应解析和日志。
+    pub async fn execute<E: Endpoint>(&self, params: &E) -> Result<E::Response> {
+        if let Some(cache) = &self.cache
+            && let Some(body) = cache.load(params).await?
+        {
+            E::check(&body)?;
+            return decode(&body);
+        }
+
+        let body = self.fetch(params).await?;
+        let response = decode(&body)?;
+        if let Some(cache) = &self.cache {
+            cache.store(params, &body).await;
+        }
+        Ok(response)
+    }
+
+    /// 按重试策略请求接口，返回通过检查的响应内容
+    async fn fetch<E: Endpoint>(&self, params: &E) -> Result<String> {
+        let mut attempt = 1;
+        loop {
+            match self.fetch_once(params).await {
+                Ok(response) => return Ok(response),
+                Err(e) if self.retry.should_retry(attempt, &e) => {
+                    let delay = self.retry.delay(attempt, &e);
+                    tracing::warn!(
+                        endpoint = E::NAME,
+                        attempt,
+                        max_attempts = self.retry.max_attempts(),
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e,
+                        "请求失败，等待后重试"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(endpoint = E::NAME, attempt, error = %e, "请求失败");
+                    return Err(e);
+                }
+            }
         }
     }
 
-    pub async fn division(
-        &self,
-        code: &str,
-        max_level: QueryLevel,
-    ) -> Result<DivisonQueryResult, Error> {
+    /// 发送一次接口请求
+    async fn fetch_once<E: Endpoint>(&self, params: &E) -> Result<String> {
+        self.limiter.acquire(E::NAME).await?;
+        let url = self.url(E::PATH);
+        let request = self.client.request(E::METHOD, &url).query(params);
+        tracing::debug!(endpoint = E::NAME, method = %E::METHOD, url = %url, "发送请求");
+        let response = request.send().await?;
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let url = response.url().to_string();
+        let body = response.text().await?;
+        if let Some(journal) = &self.journal {
+            journal.record(params, &url, status, &body);
+        }
+        if !status.is_success() {
+            return Err(Error::status(status, &body, retry_after));
+        }
+        E::check(&body)?;
+        Ok(body)
+    }
+
+    pub async fn division(&self, code: &str, max_level: QueryLevel) -> Result<DivisonQueryResult> {
         let req = DivisionQueryParams::latest(code, max_level);
-        let cli: reqwest::RequestBuilder = self.client.get(DIVISION_QUERY_URL).query(&req);
-        let response = cli.send().await?.json::<DivisionQueryResponse>().await?;
+        let response = self.execute(&req).await?;
         Ok(response.data)
     }
 
-    pub async fn search(params: &SearchParams) -> Result<Vec<Record>, Error> {
-        let client = Client::new();
+    /// 搜索地名，仅返回 `params` 指定的一页结果
+    pub async fn search(&self, params: &SearchParams) -> Result<Vec<Record>> {
+        Ok(self.search_page(params).await?.records)
+    }
+
+    /// 搜索地名，返回一页结果及数据总数
+    pub async fn search_page(&self, params: &SearchParams) -> Result<SearchResponse> {
+        self.execute(params).await
+    }
+
+    /// 搜索地名并自动翻页，逐条返回结果
+    ///
+    /// 从 `params` 指定的页码（默认第 1 页）开始，每页大小默认为 [`DEFAULT_PAGE_SIZE`]。
+    pub async fn search_stream(&self, params: &SearchParams) -> Result<SearchStream<'_>> {
+        SearchStream::start(self, params).await
+    }
+
+    /// 搜索地名并自动翻页，取回全部结果
+    ///
+    /// 服务器限制返回数量时不会报错，需通过 [`SearchResults::is_truncated`] 检查。
+    pub async fn search_all(&self, params: &SearchParams) -> Result<SearchResults> {
+        let mut stream = self.search_stream(params).await?;
+        let mut records = Vec::with_capacity(stream.total());
+        while let Some(record) = stream.next().await {
+            records.push(record?);
+        }
+        Ok(SearchResults {
+            records,
+            total: stream.total(),
+            skipped: stream.skipped(),
+        })
+    }
+
+    pub async fn details(&self, id: &str) -> Result<DetailsQueryResponse> {
+        let req = DetailsQueryParams::new(id.to_string());
+        self.execute(&req).await
+    }
+}
 
-        // 创建一个新的参数对象，确保行政区划代码正确
-        let mut query_params = std::collections::HashMap::new();
-        query_params.insert("stName".to_string(), params.st_name().to_string());
+/// [`Cli`] 构建器
+///
+/// 可配置服务根地址、超时、User-Agent、默认请求头和 HTTP 代理，
+/// 以便同一套代码可以访问正式服务、镜像服务或本地测试服务。
+///
+/// ```no_run
+/// use henan_toponym::api::Cli;
+/// use std::time::Duration;
+///
+/// let cli = Cli::builder()
+///     .base_url("http://127.0.0.1:8080/9095")
+///     .connect_timeout(Duration::from_secs(5))
+///     .read_timeout(Duration::from_secs(30))
+///     .user_agent("henan-toponym/0.1")
+///     .build()
+///     .expect("构建客户端失败");
+/// ```
+#[derive(Debug)]
+pub struct CliBuilder {
+    base_url: String,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    default_headers: HeaderMap,
+    proxy: Option<String>,
+    cookie_store: bool,
+    retry: RetryPolicy,
+    rate_limit: Option<RateLimit>,
+    endpoint_rate_limits: HashMap<&'static str, RateLimit>,
+    daily_quota: Option<u64>,
+    cache: Option<CacheConfig>,
+    journal: Option<PathBuf>,
+}
 
-        // 使用简化的行政区划代码
-        if let Some(code) = params.simplified_code() {
-            query_params.insert("code".to_string(), code);
+impl Default for CliBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            user_agent: None,
+            default_headers: HeaderMap::new(),
+            proxy: None,
+            cookie_store: true,
+            retry: RetryPolicy::default(),
+            rate_limit: None,
+            endpoint_rate_limits: HashMap::new(),
+            daily_quota: None,
+            cache: None,
+            journal: None,
         }
+    }
+}
+
+impl CliBuilder {
+    /// 服务根地址，默认为 [`DEFAULT_BASE_URL`]
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// 建立连接的超时时间
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 读取响应的超时时间
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// 整个请求（连接、发送、读取）的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 自定义 User-Agent
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// 添加一个默认请求头
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// 添加多个默认请求头
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers.extend(headers);
+        self
+    }
+
+    /// HTTP 代理地址，如 `http://127.0.0.1:7890`
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// 是否在请求之间保存 Cookie，默认开启
+    pub fn cookie_store(mut self, enable: bool) -> Self {
+        self.cookie_store = enable;
+        self
+    }
+
+    /// 重试策略，默认为 [`RetryPolicy::default`]
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 所有请求共享的速率限制，默认不限速
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// 单个接口的速率限制，`endpoint` 为 [`Endpoint::NAME`]，如 `"details"`
+    pub fn endpoint_rate_limit(mut self, endpoint: &'static str, limit: RateLimit) -> Self {
+        self.endpoint_rate_limits.insert(endpoint, limit);
+        self
+    }
+
+    /// 每日请求次数上限（按北京时间自然日计数，包括重试）
+    ///
+    /// 计数只保存在内存中，进程重启后重新计数；多次运行时需要自行控制总量
+    pub fn daily_quota(mut self, limit: u64) -> Self {
+        self.daily_quota = Some(limit);
+        self
+    }
+
+    /// 启用磁盘响应缓存，默认不缓存
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    /// 把每次请求的原始响应追加记录到 NDJSON 文件，默认不记录
+    pub fn journal(mut self, path: impl Into<PathBuf>) -> Self {
+        self.journal = Some(path.into());
+        self
+    }
 
-        if let Some(place_type_code) = params.place_type_code() {
-            query_params.insert("PlaceTypeCode".to_string(), place_type_code.to_string());
+    /// 构建客户端
+    pub fn build(self) -> Result<Cli> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        Url::parse(&base_url)
+            .map_err(|e| Error::Config(format!("服务地址 {} 无效: {}", base_url, e)))?;
+
+        let mut builder = Client::builder()
+            .default_headers(self.default_headers)
+            .cookie_store(self.cookie_store);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
         }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            let proxy = Proxy::all(&proxy)
+                .map_err(|e| Error::Config(format!("代理地址 {} 无效: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| Error::Config(format!("创建 HTTP 客户端失败: {}", e)))?;
+
+        Ok(Cli {
+            client,
+            base_url,
+            retry: self.retry,
+            limiter: Arc::new(RateLimiter::new(
+                self.rate_limit,
+                self.endpoint_rate_limits,
+                self.daily_quota,
+            )),
+            cache: self
+                .cache
+                .map(|config| Arc::new(ResponseCache::new(config))),
+            journal: match self.journal {
+                Some(path) => Some(Arc::new(Journal::open(path)?)),
+                None => None,
+            },
+        })
+    }
+}
+
+/// 解析响应头 `Retry-After`，仅支持秒数形式
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn mock_cli(server: &MockServer) -> Cli {
+        Cli::builder()
+            .base_url(format!("{}/9095/", server.uri()))
+            .user_agent("henan-toponym-test")
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_base_url() {
+        let result = Cli::builder().base_url("not a url").build();
+        assert!(
+            matches!(result, Err(Error::Config(_))),
+            "{:?}",
+            result.err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_division_against_mock_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/9095/xzqh/getList"))
+            .and(query_param("code", "410000000000"))
+            .and(query_param("maxLevel", "0"))
+            .and(header("user-agent", "henan-toponym-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"data":{"code":"410000000000","name":"河南省","level":1,"type":"省","children":[]},"message":"查询成功","status":200,"total":1,"tag":null}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let cli = mock_cli(&server);
+        let data = cli
+            .division("410000000000", QueryLevel::Current)
+            .await
+            .unwrap();
+        assert_eq!(data.name, "河南省");
+    }
 
-        if let Some(year) = params.year() {
-            query_params.insert("year".to_string(), year.to_string());
+    #[tokio::test]
+    async fn test_division_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(path("/9095/xzqh/getList"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"data":null,"message":"参数错误","status":500}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let cli = mock_cli(&server);
+        match cli.division("41", QueryLevel::Current).await {
+            Err(Error::Api { status, message }) => {
+                assert_eq!(status, 500);
+                assert_eq!(message.as_deref(), Some("参数错误"));
+            }
+            other => panic!("错误类型不正确: {:?}", other),
         }
+    }
 
-        if let Some(search_type) = params.search_type() {
-            let type_str = match search_type {
-                SearchType::Exact => "精确",
-                SearchType::Fuzzy => "模糊",
-            };
-            query_params.insert("searchType".to_string(), type_str.to_string());
+    #[tokio::test]
+    async fn test_search_against_mock_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/9095/stname/listPub"))
+            .and(query_param("stName", "唐庄村"))
+            .and(query_param("code", "410122"))
+            .and(query_param("searchType", "精确"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"records":[{"id":"1","place_code":"410122","standard_name":"唐庄村","roman_alphabet_spelling":"Tangzhuang Cun","place_type":"农村居民点","place_type_code":"22200"}],"total":1}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let params = SearchParamsBuilder::default()
+            .st_name("唐庄村")
+            .code("410122000000")
+            .search_type(SearchType::Exact)
+            .build()
+            .unwrap();
+        let records = mock_cli(&server).search(&params).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].standard_name, "唐庄村");
+    }
+
+    fn search_page_body(ids: std::ops::Range<usize>, total: usize) -> String {
+        let records: Vec<String> = ids
+            .map(|id| {
+                format!(
+                    r#"{{"id":"{id}","place_code":"{id}","standard_name":"村{id}","roman_alphabet_spelling":"Cun","place_type":"农村居民点","place_type_code":"22200"}}"#
+                )
+            })
+            .collect();
+        format!(r#"{{"records":[{}],"total":{}}}"#, records.join(","), total)
+    }
+
+    #[tokio::test]
+    async fn test_search_all_walks_pages() {
+        let server = MockServer::start().await;
+        for (page, ids) in [("1", 0..2), ("2", 2..4), ("3", 4..5)] {
+            Mock::given(path("/9095/stname/listPub"))
+                .and(query_param("page", page))
+                .and(query_param("size", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(search_page_body(ids, 5)))
+                .expect(1)
+                .mount(&server)
+                .await;
         }
 
-        if let Some(page) = params.page() {
-            query_params.insert("page".to_string(), page.to_string());
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .size(2)
+            .build()
+            .unwrap();
+        let cli = mock_cli(&server);
+        let results = cli.search_all(&params).await.unwrap();
+        assert_eq!(results.total, 5);
+        assert_eq!(results.records.len(), 5);
+        assert!(!results.is_truncated());
+    }
+
+    #[tokio::test]
+    async fn test_search_all_reports_truncation() {
+        let server = MockServer::start().await;
+        Mock::given(path("/9095/stname/listPub"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(search_page_body(0..2, 5)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/listPub"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(search_page_body(0..0, 5)))
+            .mount(&server)
+            .await;
+
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .size(2)
+            .build()
+            .unwrap();
+        let cli = mock_cli(&server);
+        let mut stream = cli.search_stream(&params).await.unwrap();
+        assert_eq!(stream.total(), 5);
+        let mut count = 0;
+        while let Some(record) = stream.next().await {
+            record.unwrap();
+            count += 1;
         }
+        assert_eq!(count, 2);
+        assert_eq!(stream.fetched(), 2);
+        assert!(stream.is_truncated());
+    }
 
-        if let Some(size) = params.size() {
-            query_params.insert("size".to_string(), size.to_string());
+    #[tokio::test]
+    async fn test_search_all_from_later_page() {
+        let server = MockServer::start().await;
+        for (page, ids) in [("2", 2..4), ("3", 4..5)] {
+            Mock::given(path("/9095/stname/listPub"))
+                .and(query_param("page", page))
+                .and(query_param("size", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(search_page_body(ids, 5)))
+                .expect(1)
+                .mount(&server)
+                .await;
         }
 
-        let request_builder = client.get(SEARCH_URL).query(&query_params);
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .page(2)
+            .size(2)
+            .build()
+            .unwrap();
+        let cli = mock_cli(&server);
+        let results = cli.search_all(&params).await.unwrap();
+        assert_eq!(results.records.len(), 3);
+        assert_eq!(results.skipped, 2);
+        assert!(!results.is_truncated());
 
-        // 发送请求
-        let response = request_builder.send().await?;
-        let status = response.status();
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .size(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            cli.search_stream(&params).await,
+            Err(Error::Config(_))
+        ));
+    }
 
-        if !status.is_success() {
-            let _ = response.text().await?;
-            return Ok(Vec::new());
+    #[tokio::test]
+    async fn test_execute_custom_endpoint() {
+        #[derive(serde::Serialize)]
+        struct Ping {
+            q: &'static str,
         }
 
-        let text = response.text().await?;
+        #[derive(serde::Deserialize)]
+        struct Pong {
+            ok: bool,
+        }
 
-        match serde_json::from_str::<SearchResponse>(&text) {
-            Ok(search_response) => Ok(search_response.records),
-            Err(_) => Ok(Vec::new()),
+        impl Endpoint for Ping {
+            type Response = Pong;
+            const NAME: &'static str = "ping";
+            const METHOD: reqwest::Method = reqwest::Method::GET;
+            const PATH: &'static str = "/ping";
         }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/9095/ping"))
+            .and(query_param("q", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok":true}"#))
+            .mount(&server)
+            .await;
+
+        let pong = mock_cli(&server).execute(&Ping { q: "1" }).await.unwrap();
+        assert!(pong.ok);
     }
 
-    pub async fn details(&self, id: &str) -> Result<DetailsQueryResponse, Error> {
-        let req = DetailsQueryParams::new(id.to_string());
-        let cli = self.client.post(DETAILS_QUERY_URL).query(&req);
-        let response = cli.send().await?.json::<DetailsQueryResponse>().await?;
-        Ok(response)
+    #[tokio::test]
+    async fn test_execute_retries_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"ethnic_minorities_writing":"","gdm":{"type":"Point","coordinates":[[113.9,34.7]]},"id":"abc","place_code":"1","place_meaning":"","place_origin":"","place_type":"农村居民点","place_type_code":"22200","province_name":"河南省","roman_alphabet_spelling":"","standard_name":"唐庄村","province":"410000000000"}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let cli = Cli::builder()
+            .base_url(format!("{}/9095", server.uri()))
+            .retry_policy(RetryPolicy::new(3).base_delay(Duration::from_millis(1)))
+            .build()
+            .unwrap();
+        let details = cli.details("abc").await.unwrap();
+        assert_eq!(details.standard_name, "唐庄村");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_details_status_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Forbidden"))
+            .mount(&server)
+            .await;
+
+        let cli = mock_cli(&server);
+        match cli.details("abc").await {
+            Err(Error::Status { status, body, .. }) => {
+                assert_eq!(status.as_u16(), 403);
+                assert_eq!(body, "Forbidden");
+            }
+            other => panic!("错误类型不正确: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_error_keeps_body() {
+        let body = "<html>blocked</html>";
+        match decode::<SearchResponse>(body) {
+            Err(Error::Decode { body: raw, .. }) => assert_eq!(raw, body),
+            other => panic!("错误类型不正确: {:?}", other),
+        }
+    }
 
     #[tokio::test]
     async fn test_division_query() {
@@ -124,7 +707,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let records = Cli::search(&params).await;
+        let records = Cli::new().search(&params).await;
         assert!(records.is_ok(), "API调用失败: {:?}", records.err());
 
         let records = records.unwrap();
@@ -146,7 +729,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let records = Cli::search(&params).await;
+        let records = Cli::new().search(&params).await;
         assert!(records.is_ok(), "API调用失败: {:?}", records.err());
 
         let records = records.unwrap();
@@ -166,7 +749,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let records = Cli::search(&params).await;
+        let records = Cli::new().search(&params).await;
         assert!(records.is_ok(), "API调用失败: {:?}", records.err());
 
         // 注意：由于API可能不支持直接使用乡镇级别的行政区划代码搜索，
@@ -192,7 +775,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let records = Cli::search(&params).await;
+        let records = Cli::new().search(&params).await;
         assert!(records.is_ok(), "API调用失败: {:?}", records.err());
 
         let records = records.unwrap();
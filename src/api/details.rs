@@ -40,16 +40,26 @@
 //! | city | 市级行政代码 | String |
 //! | province | 省级行政代码 | String |
 //!
-//! gdm
+//! gdm（见 [`Geometry`]）
 //! | 参数名称 | 描述 | 类型 |
 //! | :---: | :---: | :---: |
 //! | type | 类型 | String |
 //! | coordinates | 坐标 | Object |
 
+use super::endpoint::Endpoint;
+use super::geometry::Geometry;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
+/// 地名详情接口的完整地址
+///
+/// 与客户端的实际请求地址 [`DEFAULT_BASE_URL`](super::cli::DEFAULT_BASE_URL) + [`DETAILS_QUERY_PATH`] 重复，不再使用
+#[deprecated(note = "使用 DEFAULT_BASE_URL 加 DETAILS_QUERY_PATH")]
 pub const DETAILS_QUERY_URL: &str = "https://dmfw.mca.gov.cn/9095/stname/detailsPub";
 
+/// 地名详情接口路径（相对于服务根地址）
+pub const DETAILS_QUERY_PATH: &str = "/stname/detailsPub";
+
 /// 地名查询请求参数
 #[derive(Debug, Clone, Serialize)]
 pub struct DetailsQueryParams {
@@ -63,6 +73,13 @@ impl DetailsQueryParams {
     }
 }
 
+impl Endpoint for DetailsQueryParams {
+    type Response = DetailsQueryResponse;
+    const NAME: &'static str = "details";
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = DETAILS_QUERY_PATH;
+}
+
 /// 地名查询响应
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DetailsQueryResponse {
@@ -103,12 +120,3 @@ pub struct DetailsQueryResponse {
     /// 省级行政代码
     pub province: String,
 }
-
-/// 空间坐标信息，GeoJson 格式
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Geometry {
-    /// 类型
-    pub r#type: String,
-    /// 坐标
-    pub coordinates: Vec<Vec<f64>>,
-}
@@ -37,11 +37,23 @@
 //! | type | 行政区划单位 | String |
 //! | children | 下级区划 | Object[] |
 
+use super::endpoint::{Endpoint, decode};
+use crate::error::{Error, Result};
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
-/// 行政区划查询接口
+/// 行政区划查询接口的完整地址
+///
+/// 与客户端的实际请求地址 [`DEFAULT_BASE_URL`](super::cli::DEFAULT_BASE_URL) + [`DIVISION_QUERY_PATH`] 重复，不再使用
+#[deprecated(note = "使用 DEFAULT_BASE_URL 加 DIVISION_QUERY_PATH")]
 pub const DIVISION_QUERY_URL: &str = "https://dmfw.mca.gov.cn/9095/xzqh/getList";
 
+/// 行政区划查询接口路径（相对于服务根地址）
+pub const DIVISION_QUERY_PATH: &str = "/xzqh/getList";
+
+/// 服务状态码：查询成功
+pub const API_SUCCESS_STATUS: i32 = 200;
+
 /// 行政区划搜索请求参数
 #[derive(Debug, Clone, Serialize)]
 pub struct DivisionQueryParams {
@@ -84,6 +96,25 @@ impl DivisionQueryParams {
     }
 }
 
+impl Endpoint for DivisionQueryParams {
+    type Response = DivisionQueryResponse;
+    const NAME: &'static str = "division";
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = DIVISION_QUERY_PATH;
+
+    /// 先检查服务状态码，失败时 data 字段通常为空，无法按完整结构解析
+    fn check(body: &str) -> Result<()> {
+        let status = decode::<ApiStatus>(body)?;
+        if status.status != API_SUCCESS_STATUS {
+            return Err(Error::Api {
+                status: status.status,
+                message: status.message,
+            });
+        }
+        Ok(())
+    }
+}
+
 /// 查询深度
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QueryLevel {
@@ -113,6 +144,13 @@ pub struct DivisionQueryResponse {
     pub tag: Option<String>,
 }
 
+/// 响应中的服务状态字段
+#[derive(Debug, Deserialize)]
+struct ApiStatus {
+    status: i32,
+    message: Option<String>,
+}
+
 /// 行政区划查询结果
 #[derive(Debug, Clone, Deserialize)]
 pub struct DivisonQueryResult {
@@ -0,0 +1,40 @@
+//! # 接口定义
+//!
+//! 每个请求参数类型通过 [`Endpoint`] 关联其请求方式、接口路径和响应类型，
+//! 由 [`Cli::execute`](super::Cli::execute) 统一发送请求、处理错误和记录日志。
+//! 新增民政部地名服务的其他接口时只需定义参数和响应结构并实现该 trait。
+
+use crate::error::{Error, Result};
+use reqwest::Method;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// 地名服务接口
+///
+/// 请求参数统一以查询字符串的形式发送。
+pub trait Endpoint: Serialize + Sync {
+    /// 响应结构
+    type Response: DeserializeOwned;
+
+    /// 接口名称，用于日志等场景
+    const NAME: &'static str;
+
+    /// 请求方式
+    const METHOD: Method;
+
+    /// 接口路径（相对于服务根地址）
+    const PATH: &'static str;
+
+    /// 在解析响应结构之前检查响应内容，如服务状态码
+    fn check(body: &str) -> Result<()> {
+        let _ = body;
+        Ok(())
+    }
+}
+
+/// 将响应内容解析为指定结构，失败时保留原始响应内容
+pub(crate) fn decode<T: DeserializeOwned>(body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|source| Error::Decode {
+        source,
+        body: body.to_string(),
+    })
+}
@@ -0,0 +1,489 @@
+//! # 空间坐标
+//!
+//! 接口返回的 `gdm` 字段为 GeoJSON 几何对象，此处按 RFC 7946 建模，
+//! 支持点、线、面及其多部件形式和几何集合，并提供中心点、外包框等常用计算。
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// 坐标位置 `[经度, 纬度]`，可能带有第三个高程分量
+pub type Position = Vec<f64>;
+
+/// GeoJSON 几何对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    /// 点
+    Point {
+        /// 坐标，兼容接口返回的 `[[经度, 纬度]]` 嵌套形式
+        #[serde(deserialize_with = "deserialize_point")]
+        coordinates: Position,
+    },
+    /// 多点
+    MultiPoint {
+        /// 坐标
+        coordinates: Vec<Position>,
+    },
+    /// 线
+    LineString {
+        /// 坐标
+        coordinates: Vec<Position>,
+    },
+    /// 多线
+    MultiLineString {
+        /// 坐标
+        coordinates: Vec<Vec<Position>>,
+    },
+    /// 面，第一个环为外环，其余为内环（洞）
+    Polygon {
+        /// 坐标
+        coordinates: Vec<Vec<Position>>,
+    },
+    /// 多面
+    MultiPolygon {
+        /// 坐标
+        coordinates: Vec<Vec<Vec<Position>>>,
+    },
+    /// 几何集合
+    GeometryCollection {
+        /// 几何对象
+        geometries: Vec<Geometry>,
+    },
+}
+
+impl Geometry {
+    /// 几何类型名称，如 `"Point"`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Point { .. } => "Point",
+            Self::MultiPoint { .. } => "MultiPoint",
+            Self::LineString { .. } => "LineString",
+            Self::MultiLineString { .. } => "MultiLineString",
+            Self::Polygon { .. } => "Polygon",
+            Self::MultiPolygon { .. } => "MultiPolygon",
+            Self::GeometryCollection { .. } => "GeometryCollection",
+        }
+    }
+
+    /// 按顺序列出全部坐标位置
+    pub fn positions(&self) -> Vec<&Position> {
+        let mut positions = Vec::new();
+        self.visit(&mut |position| positions.push(position));
+        positions
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Position)) {
+        match self {
+            Self::Point { coordinates } => f(coordinates),
+            Self::MultiPoint { coordinates } | Self::LineString { coordinates } => {
+                coordinates.iter().for_each(f)
+            }
+            Self::MultiLineString { coordinates } | Self::Polygon { coordinates } => {
+                coordinates.iter().flatten().for_each(f)
+            }
+            Self::MultiPolygon { coordinates } => {
+                coordinates.iter().flatten().flatten().for_each(f)
+            }
+            Self::GeometryCollection { geometries } => {
+                geometries.iter().for_each(|geometry| geometry.visit(f))
+            }
+        }
+    }
+
+    /// 修改全部坐标位置，如坐标系转换
+    pub fn map_positions(&mut self, f: &mut impl FnMut(&mut Position)) {
+        match self {
+            Self::Point { coordinates } => f(coordinates),
+            Self::MultiPoint { coordinates } | Self::LineString { coordinates } => {
+                coordinates.iter_mut().for_each(f)
+            }
+            Self::MultiLineString { coordinates } | Self::Polygon { coordinates } => {
+                coordinates.iter_mut().flatten().for_each(f)
+            }
+            Self::MultiPolygon { coordinates } => {
+                coordinates.iter_mut().flatten().flatten().for_each(f)
+            }
+            Self::GeometryCollection { geometries } => geometries
+                .iter_mut()
+                .for_each(|geometry| geometry.map_positions(f)),
+        }
+    }
+
+    /// 第一个坐标点 `(经度, 纬度)`
+    pub fn first_point(&self) -> Option<(f64, f64)> {
+        self.positions().into_iter().find_map(lon_lat)
+    }
+
+    /// 外包框 `[最小经度, 最小纬度, 最大经度, 最大纬度]`
+    pub fn bbox(&self) -> Option<[f64; 4]> {
+        self.positions()
+            .into_iter()
+            .filter_map(lon_lat)
+            .fold(None, |bbox, (x, y)| {
+                Some(match bbox {
+                    None => [x, y, x, y],
+                    Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                })
+            })
+    }
+
+    /// 中心点 `(经度, 纬度)`
+    ///
+    /// 面按面积加权，线按长度加权，点取平均值；几何集合只计算维度最高的部分。
+    /// 面积或长度为零的退化情况按低一维处理。
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let mut acc = [Centroid::default(); 3];
+        self.accumulate(&mut acc);
+        acc.iter().rev().find_map(Centroid::value)
+    }
+
+    /// 转换为 WKT（Well-Known Text）表示，只保留经纬度两个分量
+    pub fn to_wkt(&self) -> String {
+        fn ring(positions: &[Position]) -> String {
+            let points: Vec<String> = positions
+                .iter()
+                .filter_map(lon_lat)
+                .map(|(x, y)| format!("{} {}", x, y))
+                .collect();
+            format!("({})", points.join(", "))
+        }
+        fn rings(rings: &[Vec<Position>]) -> String {
+            let rings: Vec<String> = rings.iter().map(|r| ring(r)).collect();
+            format!("({})", rings.join(", "))
+        }
+        fn tagged(tag: &str, body: String) -> String {
+            if body == "()" {
+                format!("{} EMPTY", tag)
+            } else {
+                format!("{} {}", tag, body)
+            }
+        }
+
+        match self {
+            Self::Point { coordinates } => tagged("POINT", ring(std::slice::from_ref(coordinates))),
+            Self::MultiPoint { coordinates } => {
+                let points: Vec<Vec<Position>> =
+                    coordinates.iter().map(|p| vec![p.clone()]).collect();
+                tagged("MULTIPOINT", rings(&points))
+            }
+            Self::LineString { coordinates } => tagged("LINESTRING", ring(coordinates)),
+            Self::MultiLineString { coordinates } => tagged("MULTILINESTRING", rings(coordinates)),
+            Self::Polygon { coordinates } => tagged("POLYGON", rings(coordinates)),
+            Self::MultiPolygon { coordinates } => {
+                let polygons: Vec<String> = coordinates.iter().map(|p| rings(p)).collect();
+                tagged("MULTIPOLYGON", format!("({})", polygons.join(", ")))
+            }
+            Self::GeometryCollection { geometries } => {
+                let geometries: Vec<String> = geometries.iter().map(Geometry::to_wkt).collect();
+                tagged("GEOMETRYCOLLECTION", format!("({})", geometries.join(", ")))
+            }
+        }
+    }
+
+    /// 转换为 WKB（Well-Known Binary，小端字节序）表示，只保留经纬度两个分量
+    ///
+    /// 空点按惯例写为 `POINT(NaN NaN)`。
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_wkb(&mut out);
+        out
+    }
+
+    fn write_wkb(&self, out: &mut Vec<u8>) {
+        fn header(out: &mut Vec<u8>, kind: u32) {
+            out.push(1);
+            out.extend(kind.to_le_bytes());
+        }
+        fn point(out: &mut Vec<u8>, (x, y): (f64, f64)) {
+            out.extend(x.to_le_bytes());
+            out.extend(y.to_le_bytes());
+        }
+        fn count(out: &mut Vec<u8>, n: usize) {
+            out.extend((n as u32).to_le_bytes());
+        }
+        fn line(out: &mut Vec<u8>, positions: &[Position]) {
+            let points: Vec<_> = positions.iter().filter_map(lon_lat).collect();
+            count(out, points.len());
+            points.into_iter().for_each(|p| point(out, p));
+        }
+        fn polygon(out: &mut Vec<u8>, rings: &[Vec<Position>]) {
+            count(out, rings.len());
+            rings.iter().for_each(|ring| line(out, ring));
+        }
+
+        match self {
+            Self::Point { coordinates } => {
+                header(out, 1);
+                point(out, lon_lat(coordinates).unwrap_or((f64::NAN, f64::NAN)));
+            }
+            Self::LineString { coordinates } => {
+                header(out, 2);
+                line(out, coordinates);
+            }
+            Self::Polygon { coordinates } => {
+                header(out, 3);
+                polygon(out, coordinates);
+            }
+            Self::MultiPoint { coordinates } => {
+                let points: Vec<_> = coordinates.iter().filter_map(lon_lat).collect();
+                header(out, 4);
+                count(out, points.len());
+                for p in points {
+                    header(out, 1);
+                    point(out, p);
+                }
+            }
+            Self::MultiLineString { coordinates } => {
+                header(out, 5);
+                count(out, coordinates.len());
+                for coordinates in coordinates {
+                    header(out, 2);
+                    line(out, coordinates);
+                }
+            }
+            Self::MultiPolygon { coordinates } => {
+                header(out, 6);
+                count(out, coordinates.len());
+                for coordinates in coordinates {
+                    header(out, 3);
+                    polygon(out, coordinates);
+                }
+            }
+            Self::GeometryCollection { geometries } => {
+                header(out, 7);
+                count(out, geometries.len());
+                geometries
+                    .iter()
+                    .for_each(|geometry| geometry.write_wkb(out));
+            }
+        }
+    }
+
+    fn accumulate(&self, acc: &mut [Centroid; 3]) {
+        match self {
+            Self::Point { coordinates } => acc[0].add_point(coordinates),
+            Self::MultiPoint { coordinates } => {
+                coordinates.iter().for_each(|p| acc[0].add_point(p))
+            }
+            Self::LineString { coordinates } => acc.accumulate_line(coordinates),
+            Self::MultiLineString { coordinates } => coordinates
+                .iter()
+                .for_each(|line| acc.accumulate_line(line)),
+            Self::Polygon { coordinates } => acc.accumulate_polygon(coordinates),
+            Self::MultiPolygon { coordinates } => coordinates
+                .iter()
+                .for_each(|polygon| acc.accumulate_polygon(polygon)),
+            Self::GeometryCollection { geometries } => geometries
+                .iter()
+                .for_each(|geometry| geometry.accumulate(acc)),
+        }
+    }
+}
+
+/// 取坐标位置的经纬度
+fn lon_lat(position: &Position) -> Option<(f64, f64)> {
+    match position.as_slice() {
+        [x, y, ..] => Some((*x, *y)),
+        _ => None,
+    }
+}
+
+/// 加权中心点累加器
+#[derive(Debug, Clone, Copy, Default)]
+struct Centroid {
+    x: f64,
+    y: f64,
+    weight: f64,
+}
+
+impl Centroid {
+    fn add(&mut self, x: f64, y: f64, weight: f64) {
+        self.x += x * weight;
+        self.y += y * weight;
+        self.weight += weight;
+    }
+
+    fn add_point(&mut self, position: &Position) {
+        if let Some((x, y)) = lon_lat(position) {
+            self.add(x, y, 1.0);
+        }
+    }
+
+    fn value(&self) -> Option<(f64, f64)> {
+        (self.weight.abs() > 0.0).then(|| (self.x / self.weight, self.y / self.weight))
+    }
+}
+
+trait Accumulate {
+    fn accumulate_line(&mut self, line: &[Position]);
+    fn accumulate_polygon(&mut self, rings: &[Vec<Position>]);
+}
+
+impl Accumulate for [Centroid; 3] {
+    fn accumulate_line(&mut self, line: &[Position]) {
+        let points: Vec<_> = line.iter().filter_map(lon_lat).collect();
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            let length = (x1 - x0).hypot(y1 - y0);
+            self[1].add((x0 + x1) / 2.0, (y0 + y1) / 2.0, length);
+        }
+        points.iter().for_each(|&(x, y)| self[0].add(x, y, 1.0));
+    }
+
+    fn accumulate_polygon(&mut self, rings: &[Vec<Position>]) {
+        for (i, ring) in rings.iter().enumerate() {
+            let points: Vec<_> = ring.iter().filter_map(lon_lat).collect();
+            // 鞋带公式计算环的有向面积和中心点
+            let mut ring_acc = Centroid::default();
+            for pair in points.windows(2) {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                let cross = x0 * y1 - x1 * y0;
+                ring_acc.x += (x0 + x1) * cross;
+                ring_acc.y += (y0 + y1) * cross;
+                ring_acc.weight += cross;
+            }
+            let area = ring_acc.weight / 2.0;
+            if area != 0.0 {
+                // 外环面积计为正，内环（洞）计为负，与环的方向无关
+                let sign = if i == 0 { 1.0 } else { -1.0 };
+                let (cx, cy) = (ring_acc.x / (6.0 * area), ring_acc.y / (6.0 * area));
+                self[2].add(cx, cy, sign * area.abs());
+            }
+            self.accumulate_line(ring);
+        }
+    }
+}
+
+/// 点坐标兼容 `[x, y]` 与 `[[x, y]]` 两种形式
+fn deserialize_point<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Position, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PointCoordinates {
+        Flat(Position),
+        Nested(Vec<Position>),
+    }
+
+    match PointCoordinates::deserialize(deserializer)? {
+        PointCoordinates::Flat(position) => Ok(position),
+        PointCoordinates::Nested(mut positions) if positions.len() == 1 => Ok(positions.remove(0)),
+        PointCoordinates::Nested(_) => Err(serde::de::Error::custom("点坐标只能包含一个位置")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Geometry {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_all_types() {
+        let cases = [
+            (r#"{"type":"Point","coordinates":[113.6,34.7]}"#, "Point"),
+            (
+                r#"{"type":"MultiPoint","coordinates":[[113.6,34.7],[113.7,34.8]]}"#,
+                "MultiPoint",
+            ),
+            (
+                r#"{"type":"LineString","coordinates":[[0,0],[1,1]]}"#,
+                "LineString",
+            ),
+            (
+                r#"{"type":"MultiLineString","coordinates":[[[0,0],[1,1]]]}"#,
+                "MultiLineString",
+            ),
+            (
+                r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#,
+                "Polygon",
+            ),
+            (
+                r#"{"type":"MultiPolygon","coordinates":[[[[0,0],[1,0],[1,1],[0,0]]]]}"#,
+                "MultiPolygon",
+            ),
+            (
+                r#"{"type":"GeometryCollection","geometries":[{"type":"Point","coordinates":[0,0]}]}"#,
+                "GeometryCollection",
+            ),
+        ];
+        for (json, type_name) in cases {
+            assert_eq!(parse(json).type_name(), type_name);
+        }
+    }
+
+    #[test]
+    fn test_nested_point_coordinates() {
+        let geometry = parse(r#"{"type":"Point","coordinates":[[113.6,34.7]]}"#);
+        assert_eq!(geometry.first_point(), Some((113.6, 34.7)));
+        assert_eq!(
+            serde_json::to_string(&geometry).unwrap(),
+            r#"{"type":"Point","coordinates":[113.6,34.7]}"#
+        );
+    }
+
+    #[test]
+    fn test_to_wkt() {
+        let cases = [
+            (
+                r#"{"type":"Point","coordinates":[113.6,34.7]}"#,
+                "POINT (113.6 34.7)",
+            ),
+            (
+                r#"{"type":"MultiPoint","coordinates":[[0,0],[1,2]]}"#,
+                "MULTIPOINT ((0 0), (1 2))",
+            ),
+            (
+                r#"{"type":"LineString","coordinates":[[0,0],[1,1]]}"#,
+                "LINESTRING (0 0, 1 1)",
+            ),
+            (
+                r#"{"type":"MultiPolygon","coordinates":[[[[0,0],[1,0],[1,1],[0,0]]]]}"#,
+                "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)))",
+            ),
+            (
+                r#"{"type":"GeometryCollection","geometries":[{"type":"Point","coordinates":[0,0]},{"type":"LineString","coordinates":[]}]}"#,
+                "GEOMETRYCOLLECTION (POINT (0 0), LINESTRING EMPTY)",
+            ),
+        ];
+        for (json, wkt) in cases {
+            assert_eq!(parse(json).to_wkt(), wkt);
+        }
+    }
+
+    #[test]
+    fn test_to_wkb() {
+        let point = parse(r#"{"type":"Point","coordinates":[1.0,2.0]}"#);
+        let mut expected = vec![1, 1, 0, 0, 0];
+        expected.extend(1.0f64.to_le_bytes());
+        expected.extend(2.0f64.to_le_bytes());
+        assert_eq!(point.to_wkb(), expected);
+
+        let line = parse(r#"{"type":"MultiLineString","coordinates":[[[0,0],[1,1]]]}"#);
+        let wkb = line.to_wkb();
+        assert_eq!(&wkb[..9], &[1, 5, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(&wkb[9..18], &[1, 2, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(wkb.len(), 18 + 4 * 8);
+    }
+
+    #[test]
+    fn test_polygon_centroid_and_bbox() {
+        // 2x2 正方形中挖去左下角 1x1 的洞
+        let geometry = parse(
+            r#"{"type":"Polygon","coordinates":[[[0,0],[2,0],[2,2],[0,2],[0,0]],[[0,0],[0,1],[1,1],[1,0],[0,0]]]}"#,
+        );
+        let (x, y) = geometry.centroid().unwrap();
+        assert!((x - 7.0 / 6.0).abs() < 1e-9 && (y - 7.0 / 6.0).abs() < 1e-9);
+        assert_eq!(geometry.bbox(), Some([0.0, 0.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn test_line_and_point_centroid() {
+        let line = parse(r#"{"type":"LineString","coordinates":[[0,0],[2,0],[2,1]]}"#);
+        let (x, y) = line.centroid().unwrap();
+        assert!((x - 4.0 / 3.0).abs() < 1e-9 && (y - 1.0 / 6.0).abs() < 1e-9);
+
+        let points = parse(r#"{"type":"MultiPoint","coordinates":[[0,0],[2,4]]}"#);
+        assert_eq!(points.centroid(), Some((1.0, 2.0)));
+        assert_eq!(points.first_point(), Some((0.0, 0.0)));
+    }
+}
@@ -0,0 +1,176 @@
+//! # 原始响应日志
+//!
+//! 以 NDJSON 格式（每行一个 JSON 对象）追加记录每一次请求的地址、参数、状态码、
+//! 时间和原始响应内容。结构体未建模的字段也会完整保留，修改数据结构后可以通过
+//! [`replay`] 从日志重新解析，无需重新访问网络。
+
+use super::endpoint::{Endpoint, decode};
+use crate::error::Result;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 一次请求的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// 接口名称
+    pub endpoint: String,
+    /// 请求方式
+    pub method: String,
+    /// 完整请求地址（包含查询字符串）
+    pub url: String,
+    /// 请求参数
+    pub params: serde_json::Value,
+    /// HTTP 状态码
+    pub status: u16,
+    /// 请求时间（Unix 时间戳，毫秒）
+    pub timestamp: u64,
+    /// 原始响应内容
+    pub body: String,
+}
+
+impl JournalEntry {
+    /// 是否为成功的响应（2xx）
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// 只追加的请求日志文件
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// 打开（或创建）日志文件，新记录追加到文件末尾
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, entry: &JournalEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().expect("日志文件锁已损坏");
+        file.write_all(&line)?;
+        file.flush()
+    }
+
+    /// 记录一次请求，写入失败只记录日志
+    pub(crate) fn record<E: Endpoint>(
+        &self,
+        params: &E,
+        url: &str,
+        status: StatusCode,
+        body: &str,
+    ) {
+        let entry = JournalEntry {
+            endpoint: E::NAME.to_string(),
+            method: E::METHOD.to_string(),
+            url: url.to_string(),
+            params: serde_json::to_value(params).unwrap_or_default(),
+            status: status.as_u16(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            body: body.to_string(),
+        };
+        if let Err(e) = self.append(&entry) {
+            tracing::warn!(path = %self.path.display(), error = %e, "写入请求日志失败");
+        }
+    }
+}
+
+/// 逐条读取日志文件中的记录
+pub fn read_journal(
+    path: impl AsRef<Path>,
+) -> io::Result<impl Iterator<Item = io::Result<JournalEntry>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?)))
+}
+
+/// 从日志中重新解析某个接口的全部成功响应
+///
+/// 按日志顺序返回，解析失败的记录以 [`Error::Decode`](crate::Error::Decode) 返回。
+pub fn replay<E: Endpoint>(
+    entries: impl IntoIterator<Item = JournalEntry>,
+) -> impl Iterator<Item = Result<E::Response>> {
+    entries
+        .into_iter()
+        .filter(|entry| entry.endpoint == E::NAME && entry.is_success())
+        .map(|entry| {
+            E::check(&entry.body)?;
+            decode(&entry.body)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{DetailsQueryParams, DivisionQueryParams, QueryLevel};
+
+    #[test]
+    fn test_journal_roundtrip_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.ndjson");
+        let journal = Journal::open(&path).unwrap();
+
+        let params = DetailsQueryParams::new("abc".to_string());
+        let body = r#"{"ethnic_minorities_writing":"","gdm":{"type":"Point","coordinates":[[113.9,34.7]]},"id":"abc","place_code":"1","place_meaning":"","place_origin":"","place_type":"农村居民点","place_type_code":"22200","province_name":"河南省","roman_alphabet_spelling":"","standard_name":"唐庄村","province":"410000000000","new_field":1}"#;
+        journal.record(
+            &params,
+            "http://localhost/stname/detailsPub?id=abc",
+            StatusCode::OK,
+            body,
+        );
+        journal.record(
+            &params,
+            "http://localhost/stname/detailsPub?id=abc",
+            StatusCode::FORBIDDEN,
+            "",
+        );
+        let division = DivisionQueryParams::latest("41", QueryLevel::Current);
+        journal.record(
+            &division,
+            "http://localhost/xzqh/getList",
+            StatusCode::OK,
+            "{}",
+        );
+
+        let entries: Vec<_> = read_journal(&path)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].params, serde_json::json!({"id": "abc"}));
+        // 未建模的字段保留在原始响应中
+        assert!(entries[0].body.contains("new_field"));
+
+        let details: Vec<_> = replay::<DetailsQueryParams>(entries).collect();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].as_ref().unwrap().standard_name, "唐庄村");
+    }
+}
@@ -4,8 +4,32 @@ pub use division::*;
 pub mod cli;
 pub use cli::*;
 
+pub mod endpoint;
+pub use endpoint::*;
+
+pub mod retry;
+pub use retry::*;
+
+pub mod rate_limit;
+pub use rate_limit::*;
+
+pub mod cache;
+pub use cache::*;
+
+pub mod journal;
+pub use journal::*;
+
 pub mod details;
 pub use details::*;
 
+pub mod geometry;
+pub use geometry::*;
+
 pub mod search;
 pub use search::*;
+
+pub mod pagination;
+pub use pagination::*;
+
+pub mod split;
+pub use split::*;
@@ -0,0 +1,163 @@
+//! # 分页搜索
+//!
+//! 地名搜索接口每次只返回一页结果，本模块根据 [`SearchResponse::total`]
+//! 自动翻页，直到取回全部数据或服务器不再返回新数据（结果被截断）。
+
+use super::{cli::Cli, search::*};
+use crate::error::{Error, Result};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+/// 未指定每页大小时使用的默认值
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// 逐条返回搜索结果的异步流
+///
+/// 创建时已取回第一页，因此 [`SearchStream::total`] 可以在遍历前得知预期总数。
+/// 遍历结束后通过 [`SearchStream::is_truncated`] 判断服务器是否限制了返回数量。
+pub struct SearchStream<'a> {
+    total: usize,
+    skipped: usize,
+    fetched: Arc<AtomicUsize>,
+    inner: BoxStream<'a, Result<Record>>,
+}
+
+impl<'a> SearchStream<'a> {
+    /// 服务器报告的数据总数
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// 目前已取回的记录数
+    pub fn fetched(&self) -> usize {
+        self.fetched.load(Ordering::Relaxed)
+    }
+
+    /// 起始页之前的记录数
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// 遍历结束后取回的记录数是否少于从起始页起应有的记录数
+    pub fn is_truncated(&self) -> bool {
+        self.skipped + self.fetched() < self.total
+    }
+
+    /// 取回第一页并创建流
+    pub(crate) async fn start(cli: &'a Cli, params: &SearchParams) -> Result<Self> {
+        let page = params.page().unwrap_or(1);
+        let size = params.size().unwrap_or(DEFAULT_PAGE_SIZE);
+        if page == 0 || size == 0 {
+            return Err(Error::Config(format!(
+                "页码和每页大小必须大于 0（page = {}, size = {}）",
+                page, size
+            )));
+        }
+        let params = params.with_page_size(page, size);
+
+        let first = cli.search_page(&params).await?;
+        let total = first.total;
+        let skipped = (page - 1).saturating_mul(size);
+        // 按数据总数计算最后一页，防止服务器忽略页码时无限翻页
+        let last_page = total.div_ceil(size).max(page);
+        let fetched = Arc::new(AtomicUsize::new(0));
+
+        let state = PageState {
+            cli,
+            params,
+            page,
+            size,
+            last_page,
+            expected: total.saturating_sub(skipped),
+            fetched: fetched.clone(),
+            first: Some(first.records),
+        };
+        let pages = stream::unfold(state, PageState::next);
+        let inner = pages
+            .flat_map(|page| match page {
+                Ok(records) => stream::iter(records.into_iter().map(Ok)).left_stream(),
+                Err(e) => stream::iter(Some(Err(e))).right_stream(),
+            })
+            .boxed();
+
+        Ok(Self {
+            total,
+            skipped,
+            fetched,
+            inner,
+        })
+    }
+}
+
+impl Stream for SearchStream<'_> {
+    type Item = Result<Record>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// 翻页状态
+struct PageState<'a> {
+    cli: &'a Cli,
+    params: SearchParams,
+    page: usize,
+    size: usize,
+    last_page: usize,
+    /// 从起始页起应取回的记录数
+    expected: usize,
+    fetched: Arc<AtomicUsize>,
+    first: Option<Vec<Record>>,
+}
+
+impl<'a> PageState<'a> {
+    async fn next(mut self) -> Option<(Result<Vec<Record>>, Self)> {
+        let records = match self.first.take() {
+            Some(records) => records,
+            None => {
+                if self.fetched.load(Ordering::Relaxed) >= self.expected
+                    || self.page > self.last_page
+                {
+                    return None;
+                }
+                let params = self.params.with_page_size(self.page, self.size);
+                match self.cli.search_page(&params).await {
+                    Ok(response) => response.records,
+                    Err(e) => {
+                        // 出错后结束翻页
+                        self.page = self.last_page + 1;
+                        return Some((Err(e), self));
+                    }
+                }
+            }
+        };
+        // 空页说明服务器不再返回数据
+        if records.is_empty() {
+            return None;
+        }
+        self.page += 1;
+        self.fetched.fetch_add(records.len(), Ordering::Relaxed);
+        Some((Ok(records), self))
+    }
+}
+
+/// 自动翻页取回的全部搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+    /// 返回结果集
+    pub records: Vec<Record>,
+    /// 服务器报告的数据总数
+    pub total: usize,
+    /// 起始页之前的记录数，从第 1 页开始时为 0
+    pub skipped: usize,
+}
+
+impl SearchResults {
+    /// 取回的记录数是否少于从起始页起应有的记录数
+    pub fn is_truncated(&self) -> bool {
+        self.skipped + self.records.len() < self.total
+    }
+}
@@ -0,0 +1,233 @@
+//! # 请求限速
+//!
+//! [`Cli`](super::Cli) 持有一个令牌桶限速器，所有请求（包括重试）发送前都需要取得令牌，
+//! 克隆出的客户端共享同一个限速器，因此多个任务并发调用时总请求速率不会超过设定值。
+//! 还可以为单个接口设置更严格的速率，以及限制每天的请求总数。
+
+use crate::error::{Error, Result};
+use crate::time::today;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 令牌桶速率
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// 每秒补充的令牌数
+    rate: f64,
+    /// 桶容量，即允许的突发请求数
+    burst: u32,
+}
+
+impl RateLimit {
+    /// 每秒 `rate` 个请求，突发容量为 1；`rate` 不是正数时返回 [`Error::Config`]
+    pub fn per_second(rate: f64) -> Result<Self> {
+        if !(rate > 0.0 && rate.is_finite()) {
+            return Err(Error::Config(format!(
+                "请求速率必须大于 0，实际为 {}",
+                rate
+            )));
+        }
+        Ok(Self { rate, burst: 1 })
+    }
+
+    /// 每分钟 `count` 个请求，突发容量为 1；`count` 为 0 时返回 [`Error::Config`]
+    pub fn per_minute(count: u32) -> Result<Self> {
+        Self::per_second(count as f64 / 60.0)
+    }
+
+    /// 允许的突发请求数
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+/// 令牌桶
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.burst as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// 预留一个令牌，返回需要等待的时间
+    ///
+    /// 令牌不足时余额记为负数，后来的请求依次排在后面等待。
+    fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().expect("限速器状态锁已损坏");
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.limit.rate).min(self.limit.burst as f64);
+        state.updated = now;
+        state.tokens -= 1.0;
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / self.limit.rate)
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 每日请求配额，按北京时间（UTC+8）自然日计数
+///
+/// 计数只保存在内存中，进程重启后从 0 开始
+#[derive(Debug)]
+struct DailyQuota {
+    limit: u64,
+    state: Mutex<(u64, u64)>,
+}
+
+impl DailyQuota {
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            state: Mutex::new((today(), 0)),
+        }
+    }
+
+    fn consume(&self) -> Result<()> {
+        let mut state = self.state.lock().expect("配额状态锁已损坏");
+        let day = today();
+        if state.0 != day {
+            *state = (day, 0);
+        }
+        if state.1 >= self.limit {
+            return Err(Error::QuotaExceeded { limit: self.limit });
+        }
+        state.1 += 1;
+        Ok(())
+    }
+
+    fn used(&self) -> u64 {
+        let state = self.state.lock().expect("配额状态锁已损坏");
+        if state.0 == today() { state.1 } else { 0 }
+    }
+}
+
+/// 客户端限速器
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    global: Option<TokenBucket>,
+    endpoints: HashMap<&'static str, TokenBucket>,
+    quota: Option<DailyQuota>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(
+        global: Option<RateLimit>,
+        endpoints: HashMap<&'static str, RateLimit>,
+        daily_quota: Option<u64>,
+    ) -> Self {
+        Self {
+            global: global.map(TokenBucket::new),
+            endpoints: endpoints
+                .into_iter()
+                .map(|(name, limit)| (name, TokenBucket::new(limit)))
+                .collect(),
+            quota: daily_quota.map(DailyQuota::new),
+        }
+    }
+
+    /// 等待直到可以向 `endpoint` 发送请求，超出每日配额时返回 [`Error::QuotaExceeded`]
+    pub async fn acquire(&self, endpoint: &str) -> Result<()> {
+        if let Some(quota) = &self.quota {
+            quota.consume()?;
+        }
+        if let Some(bucket) = &self.global {
+            bucket.acquire().await;
+        }
+        if let Some(bucket) = self.endpoints.get(endpoint) {
+            bucket.acquire().await;
+        }
+        Ok(())
+    }
+
+    /// 今日已使用的请求数（未设置每日配额时为 `None`）
+    pub fn daily_used(&self) -> Option<u64> {
+        self.quota.as_ref().map(DailyQuota::used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_spacing() {
+        let limiter = RateLimiter::new(
+            Some(RateLimit::per_second(2.0).unwrap().burst(2)),
+            HashMap::new(),
+            None,
+        );
+        let start = Instant::now();
+        for _ in 0..6 {
+            limiter.acquire("search").await.unwrap();
+        }
+        // 前 2 个请求使用突发容量，其余 4 个每 0.5 秒一个
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_endpoint_limit() {
+        let limiter = RateLimiter::new(
+            None,
+            HashMap::from([("details", RateLimit::per_second(1.0).unwrap())]),
+            None,
+        );
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire("search").await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        for _ in 0..3 {
+            limiter.acquire("details").await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_invalid_rate() {
+        assert!(matches!(RateLimit::per_second(0.0), Err(Error::Config(_))));
+        assert!(matches!(
+            RateLimit::per_second(f64::NAN),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(RateLimit::per_minute(0), Err(Error::Config(_))));
+        assert!(RateLimit::per_minute(40).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_daily_quota() {
+        let limiter = RateLimiter::new(None, HashMap::new(), Some(2));
+        limiter.acquire("search").await.unwrap();
+        limiter.acquire("details").await.unwrap();
+        let result = limiter.acquire("details").await;
+        assert!(matches!(result, Err(Error::QuotaExceeded { limit: 2 })));
+        assert_eq!(limiter.daily_used(), Some(2));
+    }
+}
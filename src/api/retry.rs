@@ -0,0 +1,190 @@
+//! # 重试策略
+//!
+//! [`RetryPolicy`] 挂在 [`Cli`](super::Cli) 上，所有接口调用失败时按同一策略重试：
+//! 指数退避（`base_delay * 2^(n-1)`，不超过 `max_delay`）加随机抖动，
+//! 并在 429/503 等响应带有 `Retry-After` 时按服务器要求等待。
+
+use crate::error::Error;
+use rand::Rng;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// 退避时间的随机抖动方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// 不加抖动，严格按指数退避等待
+    None,
+    /// 在 `[0, 退避时间]` 内均匀随机
+    #[default]
+    Full,
+    /// 等待一半退避时间，另一半在 `[0, 退避时间/2]` 内随机
+    Equal,
+}
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: Jitter,
+    respect_retry_after: bool,
+    retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    /// 最多尝试 3 次，退避时间 1 秒起、最长 30 秒，完全随机抖动
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: Jitter::Full,
+            respect_retry_after: true,
+            retryable: is_retryable,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 最多尝试 `max_attempts` 次（包含第一次请求），其余参数使用默认值
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// 不重试
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    /// 第一次重试前的退避时间
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// 退避时间上限（不限制 `Retry-After`）
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// 随机抖动方式
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 是否按响应头 `Retry-After` 等待，默认开启
+    pub fn respect_retry_after(mut self, enable: bool) -> Self {
+        self.respect_retry_after = enable;
+        self
+    }
+
+    /// 判断错误是否可以重试，默认为 [`is_retryable`]
+    pub fn retryable(mut self, retryable: fn(&Error) -> bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// 最多尝试次数
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// 第 `attempt` 次请求失败后是否应当重试
+    pub fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && (self.retryable)(error)
+    }
+
+    /// 第 `attempt` 次请求失败后的等待时间
+    pub fn delay(&self, attempt: u32, error: &Error) -> Duration {
+        if self.respect_retry_after
+            && let Some(retry_after) = error.retry_after()
+        {
+            return retry_after;
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let millis = backoff.as_millis() as u64;
+        let millis = match self.jitter {
+            Jitter::None => millis,
+            Jitter::Full => rand::rng().random_range(0..=millis),
+            Jitter::Equal => millis / 2 + rand::rng().random_range(0..=millis / 2),
+        };
+        Duration::from_millis(millis)
+    }
+}
+
+/// 默认的可重试错误：网络传输错误、429 以及 5xx 状态码
+pub fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::Transport(e) => !e.is_builder() && !e.is_decode(),
+        Error::Status { status, .. } => {
+            *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_error(code: u16, retry_after: Option<Duration>) -> Error {
+        Error::status(StatusCode::from_u16(code).unwrap(), "", retry_after)
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = RetryPolicy::new(10)
+            .base_delay(Duration::from_millis(500))
+            .max_delay(Duration::from_secs(5))
+            .jitter(Jitter::None);
+        let err = status_error(503, None);
+        assert_eq!(policy.delay(1, &err), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, &err), Duration::from_secs(1));
+        assert_eq!(policy.delay(3, &err), Duration::from_secs(2));
+        assert_eq!(policy.delay(5, &err), Duration::from_secs(5));
+        assert_eq!(policy.delay(40, &err), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_jitter_within_bounds() {
+        let err = status_error(503, None);
+        let policy = RetryPolicy::new(3)
+            .base_delay(Duration::from_secs(2))
+            .jitter(Jitter::Equal);
+        for _ in 0..100 {
+            let delay = policy.delay(1, &err);
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let err = status_error(429, Some(Duration::from_secs(60)));
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, &err), Duration::from_secs(60));
+        let policy = policy.respect_retry_after(false).jitter(Jitter::None);
+        assert_eq!(policy.delay(1, &err), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let policy = RetryPolicy::new(3);
+        assert!(policy.should_retry(1, &status_error(503, None)));
+        assert!(policy.should_retry(2, &status_error(429, None)));
+        assert!(!policy.should_retry(3, &status_error(503, None)));
+        assert!(!policy.should_retry(1, &status_error(404, None)));
+        let api = Error::Api {
+            status: 500,
+            message: None,
+        };
+        assert!(!policy.should_retry(1, &api));
+        assert!(!RetryPolicy::none().should_retry(1, &status_error(503, None)));
+    }
+}
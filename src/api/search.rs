@@ -48,12 +48,21 @@
 //! | gdm | 空间坐标信息，GeoJson格式 | Object |  
 //!
 
-use super::details::Geometry;
+use super::geometry::Geometry;
+use super::endpoint::Endpoint;
 use derive_builder::Builder;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
+/// 地名搜索接口的完整地址
+///
+/// 与客户端的实际请求地址 [`DEFAULT_BASE_URL`](super::cli::DEFAULT_BASE_URL) + [`SEARCH_PATH`] 重复，不再使用
+#[deprecated(note = "使用 DEFAULT_BASE_URL 加 SEARCH_PATH")]
 pub const SEARCH_URL: &str = "https://dmfw.mca.gov.cn/9095/stname/listPub";
 
+/// 地名搜索接口路径（相对于服务根地址）
+pub const SEARCH_PATH: &str = "/stname/listPub";
+
 /// 地名搜索请求参数
 #[derive(Debug, Clone, Serialize, Builder)]
 #[builder(pattern = "mutable")]
@@ -74,9 +83,12 @@ pub struct SearchParams {
     #[builder(setter(strip_option), default)]
     #[serde(rename = "searchType", skip_serializing_if = "Option::is_none")]
     search_type: Option<SearchType>,
-    /// 行政区划代码
+    /// 行政区划代码，序列化时转换为简化代码
     #[builder(setter(strip_option, into), default)]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_simplified_code"
+    )]
     code: Option<String>,
     /// 页码
     #[builder(setter(strip_option), default)]
@@ -119,32 +131,7 @@ impl SearchParams {
     /// API只接受短格式的行政区划代码，如"41"表示河南省，"4103"表示洛阳市
     /// 此函数将完整的18位代码转换为短格式
     pub fn simplified_code(&self) -> Option<String> {
-        self.code.as_ref().map(|code| {
-            // 如果代码长度大于6位，则取前几位作为简化代码
-            if code.len() > 6 {
-                // 省级代码：前2位
-                // 市级代码：前4位
-                // 区县级代码：前6位
-                // 乡镇级别代码：也使用区县级代码（前6位），因为API不支持直接使用乡镇级别代码
-                if code.starts_with("41") {
-                    // 河南省
-                    if code.len() >= 4 && &code[2..4] != "00" {
-                        if code.len() >= 6 && &code[4..6] != "00" {
-                            // 对于乡镇级别及以下的代码，统一使用区县级代码
-                            code[0..6].to_string() // 区县级
-                        } else {
-                            code[0..4].to_string() // 市级
-                        }
-                    } else {
-                        code[0..2].to_string() // 省级
-                    }
-                } else {
-                    code.clone() // 其他情况保持不变
-                }
-            } else {
-                code.clone() // 已经是短格式，保持不变
-            }
-        })
+        self.code.as_deref().map(simplify_code)
     }
 
     /// 获取页码
@@ -156,6 +143,92 @@ impl SearchParams {
     pub fn size(&self) -> Option<usize> {
         self.size
     }
+
+    /// 复制参数并替换标准名称
+    pub(crate) fn with_st_name(&self, st_name: String) -> Self {
+        Self {
+            st_name,
+            ..self.clone()
+        }
+    }
+
+    /// 复制参数并替换类别代码
+    pub(crate) fn with_place_type_code(&self, place_type_code: String) -> Self {
+        Self {
+            place_type_code: Some(place_type_code),
+            ..self.clone()
+        }
+    }
+
+    /// 复制参数并替换匹配方式
+    pub(crate) fn with_search_type(&self, search_type: SearchType) -> Self {
+        Self {
+            search_type: Some(search_type),
+            ..self.clone()
+        }
+    }
+
+    /// 复制参数并替换行政区划代码
+    pub(crate) fn with_code(&self, code: String) -> Self {
+        Self {
+            code: Some(code),
+            ..self.clone()
+        }
+    }
+
+    /// 复制参数并指定页码和每页大小
+    pub(crate) fn with_page_size(&self, page: usize, size: usize) -> Self {
+        Self {
+            page: Some(page),
+            size: Some(size),
+            ..self.clone()
+        }
+    }
+}
+
+impl Endpoint for SearchParams {
+    type Response = SearchResponse;
+    const NAME: &'static str = "search";
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = SEARCH_PATH;
+}
+
+/// 将完整的行政区划代码转换为API接受的短格式
+pub(crate) fn simplify_code(code: &str) -> String {
+    // 如果代码长度大于6位，则取前几位作为简化代码
+    if code.len() > 6 {
+        // 省级代码：前2位
+        // 市级代码：前4位
+        // 区县级代码：前6位
+        // 乡镇级别代码：也使用区县级代码（前6位），因为API不支持直接使用乡镇级别代码
+        if code.starts_with("41") {
+            // 河南省
+            if code.len() >= 4 && &code[2..4] != "00" {
+                if code.len() >= 6 && &code[4..6] != "00" {
+                    // 对于乡镇级别及以下的代码，统一使用区县级代码
+                    code[0..6].to_string() // 区县级
+                } else {
+                    code[0..4].to_string() // 市级
+                }
+            } else {
+                code[0..2].to_string() // 省级
+            }
+        } else {
+            code.to_string() // 其他情况保持不变
+        }
+    } else {
+        code.to_string() // 已经是短格式，保持不变
+    }
+}
+
+fn serialize_simplified_code<S: serde::Serializer>(
+    code: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match code {
+        Some(code) => serializer.serialize_str(&simplify_code(code)),
+        None => serializer.serialize_none(),
+    }
 }
 
 /// 匹配方式 精确/模糊
@@ -180,7 +253,7 @@ pub struct SearchResponse {
 }
 
 /// 返回结果集
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Record {
     /// 数据ID
     pub id: String,
@@ -215,3 +288,34 @@ pub struct Record {
     #[serde(flatten)]
     pub other: std::collections::HashMap<String, serde_json::Value>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_params_query_encoding() {
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .place_type_code("22200")
+            .code("410122000000")
+            .search_type(SearchType::Fuzzy)
+            .page(2)
+            .size(50)
+            .build()
+            .unwrap();
+        let query = serde_urlencoded::to_string(&params).unwrap();
+        assert_eq!(
+            query,
+            "stName=&PlaceTypeCode=22200&searchType=%E6%A8%A1%E7%B3%8A&code=410122&page=2&size=50"
+        );
+    }
+
+    #[test]
+    fn test_simplified_code() {
+        assert_eq!(simplify_code("410000000000"), "41");
+        assert_eq!(simplify_code("410300000000"), "4103");
+        assert_eq!(simplify_code("410726104000"), "410726");
+        assert_eq!(simplify_code("4103"), "4103");
+    }
+}
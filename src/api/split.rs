@@ -0,0 +1,239 @@
+//! # 查询拆分
+//!
+//! 当一次搜索的结果数超过服务器的返回上限时（翻页到底仍少于数据总数），
+//! 按地名类别子代码、名称字符或下级行政区划把查询拆分为多个更小的查询，
+//! 合并结果并按 [`Record::id`] 去重。
+
+use super::{cli::Cli, division::QueryLevel, pagination::SearchResults, search::*};
+use crate::error::Result;
+use futures::future::BoxFuture;
+use std::collections::HashSet;
+
+/// 按名称拆分时默认使用的字符（村名常用字及常见姓氏）
+pub const DEFAULT_NAME_CHARS: &[&str] = &[
+    "村", "庄", "营", "寨", "屯", "集", "店", "楼", "湾", "沟", "岗", "坡", "园", "桥", "河", "岭",
+    "口", "头", "东", "西", "南", "北", "上", "下", "前", "后", "大", "小", "新", "老", "王", "李",
+    "张", "刘", "陈", "杨", "赵", "黄", "周", "吴", "孙", "马", "朱", "胡", "郭", "何", "高", "郑",
+];
+
+/// 查询拆分策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// 按地名类别子代码拆分，如 `22200` 拆分为 `22210`..`22290`
+    PlaceTypeSubCode,
+    /// 按名称字符拆分，在当前名称后追加各字符进行模糊搜索
+    NameChars(Vec<String>),
+    /// 按下级行政区划拆分（省拆分为市、市拆分为县），需要额外的行政区划查询
+    AdminCode,
+}
+
+impl SplitStrategy {
+    /// 默认拆分顺序：地名类别子代码、下级行政区划、名称字符
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::PlaceTypeSubCode,
+            Self::AdminCode,
+            Self::NameChars(DEFAULT_NAME_CHARS.iter().map(|c| c.to_string()).collect()),
+        ]
+    }
+
+    /// 按策略生成子查询，无法拆分时返回空列表
+    async fn split(&self, cli: &Cli, params: &SearchParams) -> Result<Vec<SearchParams>> {
+        let children = match self {
+            Self::PlaceTypeSubCode => params
+                .place_type_code()
+                .map(place_type_sub_codes)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|code| params.with_place_type_code(code))
+                .collect(),
+            Self::NameChars(chars) => chars
+                .iter()
+                .map(|c| {
+                    params
+                        .with_st_name(format!("{}{}", params.st_name(), c))
+                        .with_search_type(SearchType::Fuzzy)
+                })
+                .collect(),
+            Self::AdminCode => {
+                let Some(code) = params.simplified_code() else {
+                    return Ok(Vec::new());
+                };
+                let division = cli
+                    .division(&format!("{:0<12}", code), QueryLevel::Child)
+                    .await?;
+                division
+                    .children
+                    .iter()
+                    .map(|child| params.with_code(child.code.clone()))
+                    // 接口不支持区县级以下代码，简化后与原代码相同的子区划无法拆分
+                    .filter(|child| child.simplified_code().as_deref() != Some(code.as_str()))
+                    .collect()
+            }
+        };
+        Ok(children)
+    }
+}
+
+/// 生成地名类别的下一级子代码
+///
+/// 类别代码末尾的 0 表示上级类别，将第一个末尾 0 依次替换为 1-9。
+fn place_type_sub_codes(code: &str) -> Vec<String> {
+    let Some(last) = code.rfind(|c| c != '0') else {
+        return Vec::new();
+    };
+    let prefix = &code[..=last];
+    let zeros = code.len() - last - 1;
+    if zeros == 0 {
+        return Vec::new();
+    }
+    (1..=9)
+        .map(|d| format!("{}{}{}", prefix, d, "0".repeat(zeros - 1)))
+        .collect()
+}
+
+/// 按 id 去重的结果集合
+#[derive(Default)]
+struct Merged {
+    ids: HashSet<String>,
+    records: Vec<Record>,
+}
+
+impl Merged {
+    fn extend(&mut self, records: Vec<Record>) {
+        for record in records {
+            if self.ids.insert(record.id.clone()) {
+                self.records.push(record);
+            }
+        }
+    }
+}
+
+impl Cli {
+    /// 搜索地名并取回全部结果，结果被截断时按 `strategies` 依次拆分查询
+    ///
+    /// 每种策略在一条拆分路径上最多使用一次；返回的 [`SearchResults::total`]
+    /// 为原始查询的数据总数，全部策略用尽后仍被截断时
+    /// [`SearchResults::is_truncated`] 返回 `true`。
+    pub async fn search_complete(
+        &self,
+        params: &SearchParams,
+        strategies: &[SplitStrategy],
+    ) -> Result<SearchResults> {
+        let mut merged = Merged::default();
+        let (total, skipped) = self
+            .search_split(params.clone(), strategies, &mut merged)
+            .await?;
+        Ok(SearchResults {
+            records: merged.records,
+            total,
+            skipped,
+        })
+    }
+
+    /// 返回本次查询的数据总数和起始页之前的记录数
+    fn search_split<'a>(
+        &'a self,
+        params: SearchParams,
+        strategies: &'a [SplitStrategy],
+        merged: &'a mut Merged,
+    ) -> BoxFuture<'a, Result<(usize, usize)>> {
+        Box::pin(async move {
+            let results = self.search_all(&params).await?;
+            let counts = (results.total, results.skipped);
+            let truncated = results.is_truncated();
+            merged.extend(results.records);
+            if !truncated {
+                return Ok(counts);
+            }
+
+            for (i, strategy) in strategies.iter().enumerate() {
+                let children = strategy.split(self, &params).await?;
+                if children.is_empty() {
+                    continue;
+                }
+                for child in children {
+                    self.search_split(child, &strategies[i + 1..], merged)
+                        .await?;
+                }
+                break;
+            }
+            Ok(counts)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_place_type_sub_codes() {
+        let codes = place_type_sub_codes("22200");
+        assert_eq!(codes.len(), 9);
+        assert_eq!(codes[0], "22210");
+        assert_eq!(codes[8], "22290");
+        assert_eq!(place_type_sub_codes("20000")[1], "22000");
+        assert!(place_type_sub_codes("22211").is_empty());
+    }
+
+    fn body(ids: &[&str], total: usize) -> String {
+        let records: Vec<String> = ids
+            .iter()
+            .map(|id| {
+                format!(
+                    r#"{{"id":"{id}","place_code":"{id}","standard_name":"村{id}","roman_alphabet_spelling":"Cun","place_type":"农村居民点","place_type_code":"22200"}}"#
+                )
+            })
+            .collect();
+        format!(r#"{{"records":[{}],"total":{}}}"#, records.join(","), total)
+    }
+
+    #[tokio::test]
+    async fn test_search_complete_splits_by_place_type() {
+        let server = MockServer::start().await;
+        // 原始查询：共 3 条，但服务器只返回 2 条
+        Mock::given(path("/9095/stname/listPub"))
+            .and(query_param("PlaceTypeCode", "22200"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body(&["a", "b"], 3)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/listPub"))
+            .and(query_param("PlaceTypeCode", "22210"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body(&["a", "c"], 2)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/listPub"))
+            .and(query_param("PlaceTypeCode", "22220"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body(&["b"], 1)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/listPub"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body(&[], 0)))
+            .with_priority(10)
+            .mount(&server)
+            .await;
+
+        let cli = Cli::builder()
+            .base_url(format!("{}/9095", server.uri()))
+            .build()
+            .unwrap();
+        let params = SearchParamsBuilder::default()
+            .st_name("")
+            .place_type_code("22200")
+            .size(2)
+            .build()
+            .unwrap();
+        let results = cli
+            .search_complete(&params, &[SplitStrategy::PlaceTypeSubCode])
+            .await
+            .unwrap();
+        let mut ids: Vec<_> = results.records.iter().map(|r| r.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert!(!results.is_truncated());
+    }
+}
@@ -0,0 +1,508 @@
+//! # 命令行
+//!
+//! `henan-toponym` 的子命令：查询行政区划、搜索地名、查询详情，以及采集、重试、导出等。
+//! 查询结果按全局选项 `--format` 打印，见 [`PrintFormat`]；进度和提示信息输出到标准错误，
+//! 标准输出只有查询结果，可以直接重定向或接管道。
+
+use crate::api::*;
+use crate::crs::Crs;
+use crate::dedup::{dedup_csv, write_conflicts};
+use crate::details::{
+    JOURNAL_PATH, OUTPUT_DIR, replay_journal, rural_settlements_details,
+    rural_settlements_harvester, test_single_county_details,
+};
+use crate::export::ExportFormat;
+use crate::filter::county_divisions;
+use crate::harvest::{HarvestSummary, STORAGE_FILE};
+use crate::job::HarvestJob;
+use crate::output::{DivisionRow, PrintFormat, print_rows};
+use crate::storage::{Storage, ToponymQuery};
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+/// 河南省行政区划代码
+const PROVINCE_CODE: &str = "410000000000";
+
+/// 河南省地名查询与采集
+#[derive(Debug, Parser)]
+#[command(name = "henan-toponym", version)]
+pub struct CommandLine {
+    /// 查询结果的输出格式
+    #[arg(long, global = true, value_enum, default_value_t = PrintFormat::Table)]
+    pub format: PrintFormat,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// 子命令
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 查询行政区划
+    Division(DivisionArgs),
+    /// 搜索地名
+    Search(SearchArgs),
+    /// 查询地名详情
+    Details {
+        /// 地名 ID，可以有多个
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// 采集地名：指定任务文件时按任务采集，否则采集 county_codes.txt 中各县的农村居民点
+    Harvest {
+        /// TOML 任务文件
+        job: Option<PathBuf>,
+        /// 只采集一个县级行政区的农村居民点，用于测试
+        #[arg(long, conflicts_with = "job")]
+        county: Option<String>,
+        /// 缓存响应，任务文件中的 [cache] 设置也可以开启
+        #[arg(long)]
+        cache: bool,
+    },
+    /// 重试上次采集最终仍失败的请求
+    Retry {
+        /// 采集时使用的任务文件，省略时为农村居民点采集
+        job: Option<PathBuf>,
+        /// 缓存响应，任务文件中的 [cache] 设置也可以开启
+        #[arg(long)]
+        cache: bool,
+    },
+    /// 从 SQLite 数据库导出地名详情
+    Export(ExportArgs),
+    /// 列出县和县级市（不含市辖区）
+    Counties(CountiesArgs),
+    /// 从请求日志离线重建农村居民点的输出文件
+    Replay {
+        /// 请求日志
+        #[arg(default_value = JOURNAL_PATH)]
+        journal: String,
+    },
+    /// 按 id 去重导出的 CSV 文件，并报告同一 id 内容不同的版本
+    Dedup {
+        /// CSV 文件
+        #[arg(default_value = "rural_settlements/all_settlements.csv")]
+        csv: PathBuf,
+    },
+}
+
+/// `division` 的参数，对应 [`DivisionQueryParams`]
+#[derive(Debug, Args)]
+pub struct DivisionArgs {
+    /// 行政区划代码
+    #[arg(default_value = PROVINCE_CODE)]
+    pub code: String,
+    /// 年份，默认为最新年版
+    #[arg(long)]
+    pub year: Option<i32>,
+    /// 查询深度：0 仅本级，1 含下级，2 含下下级
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=2))]
+    pub max_level: u8,
+}
+
+/// `search` 的参数，对应 [`SearchParams`]
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// 标准名称，省略时不限名称
+    #[arg(default_value = "")]
+    pub name: String,
+    /// 地名类别代码，例如 22200
+    #[arg(long)]
+    pub place_type_code: Option<String>,
+    /// 年份
+    #[arg(long)]
+    pub year: Option<i32>,
+    /// 匹配方式
+    #[arg(long, value_enum)]
+    pub search_type: Option<SearchTypeArg>,
+    /// 行政区划代码
+    #[arg(long)]
+    pub code: Option<String>,
+    /// 页码
+    #[arg(long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub page: usize,
+    /// 每页大小
+    #[arg(long, default_value_t = 20, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub size: usize,
+    /// 取回全部结果，超过接口上限时自动拆分查询
+    #[arg(long, conflicts_with = "page")]
+    pub all: bool,
+}
+
+/// 匹配方式，见 [`SearchType`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchTypeArg {
+    /// 精确
+    Exact,
+    /// 模糊
+    Fuzzy,
+}
+
+impl From<SearchTypeArg> for SearchType {
+    fn from(search_type: SearchTypeArg) -> Self {
+        match search_type {
+            SearchTypeArg::Exact => Self::Exact,
+            SearchTypeArg::Fuzzy => Self::Fuzzy,
+        }
+    }
+}
+
+/// `export` 的参数
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// 输出文件，Parquet 为输出目录
+    pub output: PathBuf,
+    /// 导出格式：csv、geojson、gpkg、shp、kml、gpx 或 parquet，省略时按扩展名推断
+    #[arg(long)]
+    pub to: Option<ExportFormat>,
+    /// SQLite 数据库
+    #[arg(long, default_value_os_t = Path::new(OUTPUT_DIR).join(STORAGE_FILE))]
+    pub db: PathBuf,
+    /// 输出坐标系，例如 wgs84、gcj02、gk3:38
+    #[arg(long, default_value = "cgcs2000")]
+    pub crs: Crs,
+    /// 名称包含此文字
+    #[arg(long)]
+    pub name: Option<String>,
+    /// 地名类别代码
+    #[arg(long)]
+    pub place_type_code: Option<String>,
+    /// 行政区划代码前缀，例如 4101
+    #[arg(long)]
+    pub region: Option<String>,
+    /// 最多导出的条数
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+/// `counties` 的参数
+#[derive(Debug, Args)]
+pub struct CountiesArgs {
+    /// 省级行政区划代码
+    #[arg(default_value = PROVINCE_CODE)]
+    pub code: String,
+    /// 年份，默认为最新年版
+    #[arg(long)]
+    pub year: Option<i32>,
+    /// 同时把前六位代码逐行写入此文件，可作为采集范围
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 执行命令
+pub async fn run(cli: CommandLine) -> Result<()> {
+    let format = cli.format;
+    let stdout = std::io::stdout();
+    match cli.command {
+        Command::Division(args) => {
+            let level = match args.max_level {
+                0 => QueryLevel::Current,
+                1 => QueryLevel::Child,
+                _ => QueryLevel::GrandChild,
+            };
+            let division = query_division(&args.code, args.year, level).await?;
+            print_rows(stdout, &DivisionRow::flatten(&division), format)?;
+        }
+        Command::Search(args) => {
+            let records = search(&args).await?;
+            print_rows(stdout, &records, format)?;
+        }
+        Command::Details { ids } => {
+            let client = crate::api::Cli::new();
+            let mut details = Vec::with_capacity(ids.len());
+            for id in &ids {
+                details.push(client.details(id).await?);
+            }
+            print_rows(stdout, &details, format)?;
+        }
+        Command::Harvest { job, county, cache } => match (job, county) {
+            (Some(job), _) => {
+                // 先检查任务设置，有问题时直接退出
+                let job = load_job(job, cache)?;
+                let summary = job.harvester().await?.run().await?;
+                print_summary(&summary);
+            }
+            (None, Some(county)) => test_single_county_details(&county, cache).await?,
+            (None, None) => rural_settlements_details(cache).await?,
+        },
+        Command::Retry { job, cache } => {
+            let harvester = match job {
+                Some(job) => load_job(job, cache)?.harvester().await?,
+                None => rural_settlements_harvester(cache)?,
+            };
+            print_summary(&harvester.retry().await?);
+        }
+        Command::Export(args) => export(&args)?,
+        Command::Counties(args) => {
+            let province = query_division(&args.code, args.year, QueryLevel::GrandChild).await?;
+            let counties = county_divisions(&province);
+            if let Some(path) = &args.output {
+                let codes: String = counties
+                    .iter()
+                    .map(|(_, county)| format!("{}\n", &county.code[..6.min(county.code.len())]))
+                    .collect();
+                std::fs::write(path, codes)?;
+                eprintln!(
+                    "已把 {} 个县级行政区划代码写入 {}",
+                    counties.len(),
+                    path.display()
+                );
+            }
+            let rows: Vec<DivisionRow> = counties
+                .into_iter()
+                .map(|(parent, county)| DivisionRow::new(county, Some(&parent.code)))
+                .collect();
+            print_rows(stdout, &rows, format)?;
+        }
+        Command::Replay { journal } => {
+            eprintln!("从请求日志重建: {}", journal);
+            replay_journal(&journal).await?;
+        }
+        Command::Dedup { csv } => dedup(&csv)?,
+    }
+    Ok(())
+}
+
+/// 查询行政区划，`year` 为空时查询最新年版
+async fn query_division(
+    code: &str,
+    year: Option<i32>,
+    level: QueryLevel,
+) -> Result<DivisonQueryResult> {
+    let params = match year {
+        Some(year) => DivisionQueryParams::new(year, code.to_string(), level),
+        None => DivisionQueryParams::latest(code, level),
+    };
+    Ok(crate::api::Cli::new().execute(&params).await?.data)
+}
+
+/// 读取任务文件，`--cache` 开启响应缓存
+fn load_job(path: PathBuf, cache: bool) -> Result<HarvestJob> {
+    let mut job = HarvestJob::load(path)?;
+    job.cache.enabled |= cache;
+    Ok(job)
+}
+
+/// 按参数搜索，数据总数输出到标准错误
+async fn search(args: &SearchArgs) -> Result<Vec<Record>> {
+    let mut builder = SearchParamsBuilder::default();
+    builder.st_name(args.name.as_str()).size(args.size);
+    if let Some(code) = &args.place_type_code {
+        builder.place_type_code(code.as_str());
+    }
+    if let Some(year) = args.year {
+        builder.year(year);
+    }
+    if let Some(search_type) = args.search_type {
+        builder.search_type(search_type.into());
+    }
+    if let Some(code) = &args.code {
+        builder.code(code.as_str());
+    }
+    if !args.all {
+        builder.page(args.page);
+    }
+    let params = builder.build()?;
+
+    let client = crate::api::Cli::new();
+    if args.all {
+        let results = client
+            .search_complete(&params, &SplitStrategy::defaults())
+            .await?;
+        if results.is_truncated() {
+            eprintln!(
+                "警告: 拆分查询后结果仍被截断，仅取回 {}/{} 条",
+                results.records.len(),
+                results.total
+            );
+        }
+        Ok(results.records)
+    } else {
+        let response = client.search_page(&params).await?;
+        eprintln!(
+            "共 {} 条，第 {} 页 {} 条",
+            response.total,
+            args.page,
+            response.records.len()
+        );
+        Ok(response.records)
+    }
+}
+
+/// 从数据库查询并导出
+fn export(args: &ExportArgs) -> Result<()> {
+    let Some(format) = args.to.or_else(|| ExportFormat::from_path(&args.output)) else {
+        bail!(
+            "无法从 {} 推断导出格式，请用 --to 指定",
+            args.output.display()
+        );
+    };
+    if !args.db.exists() {
+        bail!("数据库 {} 不存在", args.db.display());
+    }
+    let storage = Storage::open(&args.db)?;
+    let mut query = ToponymQuery::new();
+    if let Some(name) = &args.name {
+        query = query.name(name);
+    }
+    if let Some(code) = &args.place_type_code {
+        query = query.place_type_code(code);
+    }
+    if let Some(region) = &args.region {
+        query = query.region(region);
+    }
+    if let Some(limit) = args.limit {
+        query = query.limit(limit);
+    }
+    let details = storage.query_details(&query)?;
+    let count = format.export(&args.output, args.crs, &details)?;
+    eprintln!(
+        "已导出 {} 条地名详情（{}）: {}",
+        count,
+        format,
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// 去重 CSV 文件，输出 `<文件名>_dedup.csv`，冲突写入 `<文件名>_conflicts.csv`
+fn dedup(input: &Path) -> Result<()> {
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let output = input.with_file_name(format!("{}_dedup.csv", stem));
+    let report = dedup_csv(input, &output)?;
+    eprintln!(
+        "记录数: {}，去重后: {}，删除完全相同的重复记录: {}",
+        report.total, report.unique, report.identical
+    );
+    if !report.conflicts.is_empty() {
+        let conflicts_path = input.with_file_name(format!("{}_conflicts.csv", stem));
+        write_conflicts(&report, &conflicts_path)?;
+        eprintln!(
+            "{} 个地名有内容不同的版本（已保留最后一个版本），详见 {}",
+            report.conflicts.len(),
+            conflicts_path.display()
+        );
+        for conflict in &report.conflicts {
+            eprintln!(
+                "  {}: {} 个版本，不一致的字段: {}",
+                conflict.id,
+                conflict.versions.len(),
+                conflict.fields.join(", ")
+            );
+        }
+    }
+    eprintln!("已写入: {}", output.display());
+    Ok(())
+}
+
+fn print_summary(summary: &HarvestSummary) {
+    if summary.counties > 0 {
+        eprintln!(
+            "完成 {}/{} 个县级行政区，搜索到 {} 个地名",
+            summary.completed_counties, summary.counties, summary.records
+        );
+    }
+    if !summary.failed_searches.is_empty() || summary.failed_details > 0 {
+        eprintln!(
+            "仍有 {} 个县级行政区搜索失败、{} 个地名详情获取失败，可运行 retry 重试",
+            summary.failed_searches.len(),
+            summary.failed_details
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_command_line() {
+        CommandLine::command().debug_assert();
+
+        let cli = CommandLine::try_parse_from([
+            "henan-toponym",
+            "search",
+            "唐庄",
+            "--place-type-code",
+            "22200",
+            "--search-type",
+            "fuzzy",
+            "--all",
+            "--format",
+            "ndjson",
+        ])
+        .unwrap();
+        assert_eq!(cli.format, PrintFormat::Ndjson);
+        let Command::Search(args) = cli.command else {
+            panic!("应为 search 命令");
+        };
+        assert_eq!(args.name, "唐庄");
+        assert_eq!(args.place_type_code.as_deref(), Some("22200"));
+        assert_eq!(args.search_type, Some(SearchTypeArg::Fuzzy));
+        assert!(args.all);
+
+        let cli = CommandLine::try_parse_from([
+            "henan-toponym",
+            "--format",
+            "csv",
+            "export",
+            "out.kml",
+            "--crs",
+            "gcj02",
+        ])
+        .unwrap();
+        let Command::Export(args) = cli.command else {
+            panic!("应为 export 命令");
+        };
+        assert_eq!(args.crs, Crs::Gcj02);
+        assert_eq!(args.db, Path::new("rural_settlements/toponyms.sqlite"));
+
+        assert!(
+            CommandLine::try_parse_from(["henan-toponym", "division", "--max-level", "3"]).is_err()
+        );
+        assert!(CommandLine::try_parse_from(["henan-toponym", "details"]).is_err());
+        assert!(
+            CommandLine::try_parse_from(["henan-toponym", "search", "--all", "--size", "0"])
+                .is_err()
+        );
+        assert!(
+            CommandLine::try_parse_from(["henan-toponym", "search", "--format", "xml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_export_from_storage() {
+        use crate::export::test_support::sample_details;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join(STORAGE_FILE);
+        Storage::open(&db)
+            .unwrap()
+            .upsert_details(None, &[sample_details()])
+            .unwrap();
+        let output = dir.path().join("settlements.geojson");
+        let args = ExportArgs {
+            output: output.clone(),
+            to: None,
+            db,
+            crs: Crs::default(),
+            name: Some("唐庄".to_string()),
+            place_type_code: None,
+            region: Some("4101".to_string()),
+            limit: None,
+        };
+        export(&args).unwrap();
+        let geojson = std::fs::read_to_string(&output).unwrap();
+        assert!(geojson.contains("唐庄村"));
+
+        let args = ExportArgs {
+            output: dir.path().join("settlements"),
+            ..args
+        };
+        assert!(export(&args).is_err());
+    }
+}
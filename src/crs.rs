@@ -0,0 +1,555 @@
+//! # 坐标参考系
+//!
+//! 接口返回的 `gdm` 坐标为 CGCS2000 经纬度。网络地图常用 GCJ-02（高德、腾讯）
+//! 或 BD-09（百度），空间分析常用 CGCS2000 高斯-克吕格 3°/6° 分带投影，
+//! 此处提供这些坐标系之间的相互转换。
+//!
+//! CGCS2000 与 WGS 84 相差在厘米级，这里视为同一坐标系。
+
+use crate::api::Geometry;
+use crate::error::{Error, Result};
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// CGCS2000 椭球长半轴
+const CGCS2000_A: f64 = 6_378_137.0;
+/// CGCS2000 椭球扁率
+const CGCS2000_F: f64 = 1.0 / 298.257_222_101;
+
+/// CGCS2000 经纬度坐标系（OGC WKT）
+const CGCS2000_GEOGCS: &str = r#"GEOGCS["China Geodetic Coordinate System 2000",DATUM["China_2000",SPHEROID["CGCS2000",6378137,298.257222101,AUTHORITY["EPSG","1024"]],AUTHORITY["EPSG","1043"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4490"]]"#;
+/// WGS 84 经纬度坐标系（OGC WKT）
+const WGS84_GEOGCS: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4326"]]"#;
+/// CGCS2000 经纬度坐标系（ESRI WKT）
+const CGCS2000_GEOGCS_ESRI: &str = r#"GEOGCS["GCS_China_Geodetic_Coordinate_System_2000",DATUM["D_China_2000",SPHEROID["CGCS2000",6378137.0,298.257222101]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+/// WGS 84 经纬度坐标系（ESRI WKT）
+const WGS84_GEOGCS_ESRI: &str = r#"GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+
+/// 高斯-克吕格投影分带宽度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZoneWidth {
+    /// 3° 分带，带号 25–45 覆盖我国
+    Three,
+    /// 6° 分带，带号 13–23 覆盖我国
+    Six,
+}
+
+impl ZoneWidth {
+    /// 分带宽度（度）
+    pub fn degrees(self) -> u8 {
+        match self {
+            Self::Three => 3,
+            Self::Six => 6,
+        }
+    }
+
+    /// 覆盖我国的带号范围
+    pub fn zones(self) -> std::ops::RangeInclusive<u8> {
+        match self {
+            Self::Three => 25..=45,
+            Self::Six => 13..=23,
+        }
+    }
+}
+
+/// 坐标参考系
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Crs {
+    /// CGCS2000 经纬度（EPSG:4490），接口数据使用的坐标系
+    #[default]
+    Cgcs2000,
+    /// WGS 84 经纬度（EPSG:4326）
+    Wgs84,
+    /// GCJ-02 经纬度（国测局加密坐标，高德、腾讯地图）
+    Gcj02,
+    /// BD-09 经纬度（百度地图）
+    Bd09,
+    /// CGCS2000 高斯-克吕格投影，东坐标带带号前缀，单位为米
+    GaussKruger {
+        /// 分带宽度
+        width: ZoneWidth,
+        /// 带号
+        zone: u8,
+    },
+}
+
+impl Crs {
+    /// 3° 分带高斯-克吕格投影
+    pub fn gauss_kruger_3(zone: u8) -> Self {
+        Self::GaussKruger {
+            width: ZoneWidth::Three,
+            zone,
+        }
+    }
+
+    /// 6° 分带高斯-克吕格投影
+    pub fn gauss_kruger_6(zone: u8) -> Self {
+        Self::GaussKruger {
+            width: ZoneWidth::Six,
+            zone,
+        }
+    }
+
+    /// 经度所在的高斯-克吕格投影带
+    pub fn gauss_kruger_containing(lon: f64, width: ZoneWidth) -> Self {
+        let zone = match width {
+            ZoneWidth::Three => (lon / 3.0).round(),
+            ZoneWidth::Six => (lon / 6.0).floor() + 1.0,
+        };
+        Self::GaussKruger {
+            width,
+            zone: zone.clamp(1.0, 120.0) as u8,
+        }
+    }
+
+    /// 是否为经纬度坐标系
+    pub fn is_geographic(self) -> bool {
+        !matches!(self, Self::GaussKruger { .. })
+    }
+
+    /// EPSG 代码；GCJ-02、BD-09 及我国范围以外的投影带没有 EPSG 代码
+    pub fn epsg(self) -> Option<u32> {
+        match self {
+            Self::Cgcs2000 => Some(4490),
+            Self::Wgs84 => Some(4326),
+            Self::Gcj02 | Self::Bd09 => None,
+            Self::GaussKruger {
+                width: ZoneWidth::Three,
+                zone,
+            } => ZoneWidth::Three
+                .zones()
+                .contains(&zone)
+                .then(|| 4513 + u32::from(zone - 25)),
+            Self::GaussKruger {
+                width: ZoneWidth::Six,
+                zone,
+            } => ZoneWidth::Six
+                .zones()
+                .contains(&zone)
+                .then(|| 4491 + u32::from(zone - 13)),
+        }
+    }
+
+    /// 投影带中央经线
+    fn central_meridian(width: ZoneWidth, zone: u8) -> f64 {
+        match width {
+            ZoneWidth::Three => 3.0 * f64::from(zone),
+            ZoneWidth::Six => 6.0 * f64::from(zone) - 3.0,
+        }
+    }
+
+    /// 坐标系定义（OGC WKT），GCJ-02 与 BD-09 按 WGS 84 描述
+    pub fn wkt(self) -> String {
+        match self {
+            Self::Cgcs2000 => CGCS2000_GEOGCS.to_string(),
+            Self::Wgs84 | Self::Gcj02 | Self::Bd09 => WGS84_GEOGCS.to_string(),
+            Self::GaussKruger { width, zone } => {
+                let authority = self
+                    .epsg()
+                    .map(|code| format!(r#",AUTHORITY["EPSG","{}"]"#, code))
+                    .unwrap_or_default();
+                format!(
+                    r#"PROJCS["{}",{},PROJECTION["Transverse_Mercator"],PARAMETER["latitude_of_origin",0],PARAMETER["central_meridian",{}],PARAMETER["scale_factor",1],PARAMETER["false_easting",{}],PARAMETER["false_northing",0],UNIT["metre",1,AUTHORITY["EPSG","9001"]],AXIS["Northing",NORTH],AXIS["Easting",EAST]{}]"#,
+                    self,
+                    CGCS2000_GEOGCS,
+                    Self::central_meridian(width, zone),
+                    false_easting(zone),
+                    authority
+                )
+            }
+        }
+    }
+
+    /// Shapefile `.prj` 使用的 ESRI WKT
+    pub fn esri_wkt(self) -> String {
+        match self {
+            Self::Cgcs2000 => CGCS2000_GEOGCS_ESRI.to_string(),
+            Self::Wgs84 | Self::Gcj02 | Self::Bd09 => WGS84_GEOGCS_ESRI.to_string(),
+            Self::GaussKruger { width, zone } => format!(
+                r#"PROJCS["CGCS2000_{}_Degree_GK_Zone_{}",{},PROJECTION["Gauss_Kruger"],PARAMETER["False_Easting",{:.1}],PARAMETER["False_Northing",0.0],PARAMETER["Central_Meridian",{:.1}],PARAMETER["Scale_Factor",1.0],PARAMETER["Latitude_Of_Origin",0.0],UNIT["Meter",1.0]]"#,
+                width.degrees(),
+                zone,
+                CGCS2000_GEOGCS_ESRI,
+                false_easting(zone),
+                Self::central_meridian(width, zone)
+            ),
+        }
+    }
+
+    /// 把本坐标系的坐标 `(x, y)` 转换到目标坐标系
+    pub fn transform(self, target: Crs, point: (f64, f64)) -> (f64, f64) {
+        if self == target {
+            return point;
+        }
+        target.forward(self.inverse(point))
+    }
+
+    /// 把几何对象从本坐标系转换到目标坐标系，高程分量保持不变
+    pub fn transform_geometry(self, target: Crs, geometry: &mut Geometry) {
+        if self == target {
+            return;
+        }
+        geometry.map_positions(&mut |position| {
+            if let [x, y, ..] = position.as_mut_slice() {
+                (*x, *y) = self.transform(target, (*x, *y));
+            }
+        });
+    }
+
+    /// 转换为 CGCS2000 经纬度
+    fn inverse(self, point: (f64, f64)) -> (f64, f64) {
+        match self {
+            Self::Cgcs2000 | Self::Wgs84 => point,
+            Self::Gcj02 => gcj02_to_wgs84(point),
+            Self::Bd09 => gcj02_to_wgs84(bd09_to_gcj02(point)),
+            Self::GaussKruger { width, zone } => gauss_kruger_inverse(
+                point,
+                Self::central_meridian(width, zone),
+                false_easting(zone),
+            ),
+        }
+    }
+
+    /// 由 CGCS2000 经纬度转换
+    fn forward(self, point: (f64, f64)) -> (f64, f64) {
+        match self {
+            Self::Cgcs2000 | Self::Wgs84 => point,
+            Self::Gcj02 => wgs84_to_gcj02(point),
+            Self::Bd09 => gcj02_to_bd09(wgs84_to_gcj02(point)),
+            Self::GaussKruger { width, zone } => gauss_kruger_forward(
+                point,
+                Self::central_meridian(width, zone),
+                false_easting(zone),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Crs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cgcs2000 => write!(f, "CGCS2000"),
+            Self::Wgs84 => write!(f, "WGS 84"),
+            Self::Gcj02 => write!(f, "GCJ-02"),
+            Self::Bd09 => write!(f, "BD-09"),
+            Self::GaussKruger { width, zone } => write!(
+                f,
+                "CGCS2000 / {}-degree Gauss-Kruger zone {}",
+                width.degrees(),
+                zone
+            ),
+        }
+    }
+}
+
+impl FromStr for Crs {
+    type Err = Error;
+
+    /// 解析坐标系名称：`cgcs2000`、`wgs84`、`gcj02`、`bd09`、`gk3:<带号>`、`gk6:<带号>`
+    /// 或 `epsg:<代码>`（4490、4326 及 CGCS2000 高斯-克吕格分带）；
+    /// 带号只接受覆盖我国的范围，见 [`ZoneWidth::zones`]
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase().replace(['-', '_', ' '], "");
+        let invalid = || Error::Config(format!("无法识别的坐标系: {}", s));
+        let crs = match name.as_str() {
+            "cgcs2000" | "epsg:4490" => Self::Cgcs2000,
+            "wgs84" | "epsg:4326" => Self::Wgs84,
+            "gcj02" => Self::Gcj02,
+            "bd09" => Self::Bd09,
+            _ => {
+                let zone = |zone: &str, width: ZoneWidth| {
+                    let zone: u8 = zone.parse().map_err(|_| invalid())?;
+                    let zones = width.zones();
+                    if !zones.contains(&zone) {
+                        return Err(Error::Config(format!(
+                            "{}° 分带的带号 {} 不在我国范围内（{}–{}）",
+                            width.degrees(),
+                            zone,
+                            zones.start(),
+                            zones.end()
+                        )));
+                    }
+                    Ok(Self::GaussKruger { width, zone })
+                };
+                if let Some(code) = name.strip_prefix("gk3:") {
+                    zone(code, ZoneWidth::Three)?
+                } else if let Some(code) = name.strip_prefix("gk6:") {
+                    zone(code, ZoneWidth::Six)?
+                } else if let Some(code) = name.strip_prefix("epsg:") {
+                    match code.parse::<u32>().map_err(|_| invalid())? {
+                        code @ 4513..=4533 => Self::gauss_kruger_3((code - 4513 + 25) as u8),
+                        code @ 4491..=4501 => Self::gauss_kruger_6((code - 4491 + 13) as u8),
+                        _ => return Err(invalid()),
+                    }
+                } else {
+                    return Err(invalid());
+                }
+            }
+        };
+        Ok(crs)
+    }
+}
+
+/// 带带号前缀的东坐标偏移
+fn false_easting(zone: u8) -> f64 {
+    f64::from(zone) * 1_000_000.0 + 500_000.0
+}
+
+/// 高斯-克吕格正算：经纬度 → `(东坐标, 北坐标)`
+fn gauss_kruger_forward((lon, lat): (f64, f64), lon0: f64, false_easting: f64) -> (f64, f64) {
+    let e2 = CGCS2000_F * (2.0 - CGCS2000_F);
+    let ep2 = e2 / (1.0 - e2);
+    let phi = lat.to_radians();
+    let (sin, cos, tan) = (phi.sin(), phi.cos(), phi.tan());
+    let n = CGCS2000_A / (1.0 - e2 * sin * sin).sqrt();
+    let t = tan * tan;
+    let c = ep2 * cos * cos;
+    let a = (lon - lon0).to_radians() * cos;
+    let m = meridian_arc(phi, e2);
+
+    let x = n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+    let y = m + n
+        * tan
+        * (a * a / 2.0
+            + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+            + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0);
+    (x + false_easting, y)
+}
+
+/// 高斯-克吕格反算：`(东坐标, 北坐标)` → 经纬度
+fn gauss_kruger_inverse((x, y): (f64, f64), lon0: f64, false_easting: f64) -> (f64, f64) {
+    let e2 = CGCS2000_F * (2.0 - CGCS2000_F);
+    let ep2 = e2 / (1.0 - e2);
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+    let mu = y / (CGCS2000_A * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin, cos, tan) = (phi1.sin(), phi1.cos(), phi1.tan());
+    let c1 = ep2 * cos * cos;
+    let t1 = tan * tan;
+    let n1 = CGCS2000_A / (1.0 - e2 * sin * sin).sqrt();
+    let r1 = CGCS2000_A * (1.0 - e2) / (1.0 - e2 * sin * sin).powf(1.5);
+    let d = (x - false_easting) / n1;
+
+    let phi = phi1
+        - (n1 * tan / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lambda = (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5)
+            / 120.0)
+        / cos;
+    (lon0 + lambda.to_degrees(), phi.to_degrees())
+}
+
+/// 赤道至纬度 `phi` 的子午线弧长
+fn meridian_arc(phi: f64, e2: f64) -> f64 {
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+    CGCS2000_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * phi).sin())
+}
+
+/// GCJ-02 使用的克拉索夫斯基椭球参数
+const KRASOVSKY_A: f64 = 6_378_245.0;
+const KRASOVSKY_EE: f64 = 0.006_693_421_622_965_943;
+
+/// 是否在 GCJ-02 加密范围以外
+fn out_of_china((lon, lat): (f64, f64)) -> bool {
+    !(72.004..=137.8347).contains(&lon) || !(0.8293..=55.8271).contains(&lat)
+}
+
+/// WGS 84 到 GCJ-02 的偏移量
+fn gcj02_delta((lon, lat): (f64, f64)) -> (f64, f64) {
+    let (x, y) = (lon - 105.0, lat - 35.0);
+    let common = (20.0 * (6.0 * x * PI).sin() + 20.0 * (2.0 * x * PI).sin()) * 2.0 / 3.0;
+    let d_lat = -100.0
+        + 2.0 * x
+        + 3.0 * y
+        + 0.2 * y * y
+        + 0.1 * x * y
+        + 0.2 * x.abs().sqrt()
+        + common
+        + (20.0 * (y * PI).sin() + 40.0 * (y / 3.0 * PI).sin()) * 2.0 / 3.0
+        + (160.0 * (y / 12.0 * PI).sin() + 320.0 * (y * PI / 30.0).sin()) * 2.0 / 3.0;
+    let d_lon = 300.0
+        + x
+        + 2.0 * y
+        + 0.1 * x * x
+        + 0.1 * x * y
+        + 0.1 * x.abs().sqrt()
+        + common
+        + (20.0 * (x * PI).sin() + 40.0 * (x / 3.0 * PI).sin()) * 2.0 / 3.0
+        + (150.0 * (x / 12.0 * PI).sin() + 300.0 * (x / 30.0 * PI).sin()) * 2.0 / 3.0;
+
+    let rad_lat = lat.to_radians();
+    let magic = 1.0 - KRASOVSKY_EE * rad_lat.sin().powi(2);
+    let sqrt_magic = magic.sqrt();
+    let d_lat = d_lat * 180.0 / ((KRASOVSKY_A * (1.0 - KRASOVSKY_EE)) / (magic * sqrt_magic) * PI);
+    let d_lon = d_lon * 180.0 / (KRASOVSKY_A / sqrt_magic * rad_lat.cos() * PI);
+    (d_lon, d_lat)
+}
+
+fn wgs84_to_gcj02(point: (f64, f64)) -> (f64, f64) {
+    if out_of_china(point) {
+        return point;
+    }
+    let (d_lon, d_lat) = gcj02_delta(point);
+    (point.0 + d_lon, point.1 + d_lat)
+}
+
+/// GCJ-02 反算没有解析解，迭代求解
+fn gcj02_to_wgs84(point: (f64, f64)) -> (f64, f64) {
+    if out_of_china(point) {
+        return point;
+    }
+    invert(point, point, wgs84_to_gcj02)
+}
+
+/// 以 `guess` 为初值迭代求 `forward` 的反函数在 `target` 处的值，精度 1e-10 度
+fn invert(
+    target: (f64, f64),
+    mut guess: (f64, f64),
+    forward: impl Fn((f64, f64)) -> (f64, f64),
+) -> (f64, f64) {
+    for _ in 0..30 {
+        let (x, y) = forward(guess);
+        let (dx, dy) = (x - target.0, y - target.1);
+        guess = (guess.0 - dx, guess.1 - dy);
+        if dx.abs() < 1e-10 && dy.abs() < 1e-10 {
+            break;
+        }
+    }
+    guess
+}
+
+const BD09_X_PI: f64 = PI * 3000.0 / 180.0;
+
+fn gcj02_to_bd09((lon, lat): (f64, f64)) -> (f64, f64) {
+    let z = lon.hypot(lat) + 0.00002 * (lat * BD09_X_PI).sin();
+    let theta = lat.atan2(lon) + 0.000003 * (lon * BD09_X_PI).cos();
+    (z * theta.cos() + 0.0065, z * theta.sin() + 0.006)
+}
+
+/// 常用的近似反算公式误差约 5 厘米，以其为初值迭代修正
+fn bd09_to_gcj02(point: (f64, f64)) -> (f64, f64) {
+    let (x, y) = (point.0 - 0.0065, point.1 - 0.006);
+    let z = x.hypot(y) - 0.00002 * (y * BD09_X_PI).sin();
+    let theta = y.atan2(x) - 0.000003 * (x * BD09_X_PI).cos();
+    invert(point, (z * theta.cos(), z * theta.sin()), gcj02_to_bd09)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close((x0, y0): (f64, f64), (x1, y1): (f64, f64), tolerance: f64) {
+        assert!(
+            (x0 - x1).abs() < tolerance && (y0 - y1).abs() < tolerance,
+            "({}, {}) != ({}, {})",
+            x0,
+            y0,
+            x1,
+            y1
+        );
+    }
+
+    #[test]
+    fn test_gcj02_and_bd09() {
+        let wgs = (116.404, 39.915);
+        let gcj = Crs::Wgs84.transform(Crs::Gcj02, wgs);
+        assert_close(gcj, (116.410_244_499_169_38, 39.916_404_281_501_64), 1e-9);
+        let bd = Crs::Gcj02.transform(Crs::Bd09, wgs);
+        assert_close(bd, (116.410_369_493_710_29, 39.921_336_993_510_22), 1e-9);
+
+        // 反算回到原坐标
+        assert_close(Crs::Gcj02.transform(Crs::Cgcs2000, gcj), wgs, 1e-8);
+        assert_close(
+            Crs::Bd09.transform(Crs::Cgcs2000, Crs::Cgcs2000.transform(Crs::Bd09, wgs)),
+            wgs,
+            1e-8,
+        );
+
+        // 境外坐标不加偏移
+        assert_eq!(
+            Crs::Wgs84.transform(Crs::Gcj02, (2.35, 48.85)),
+            (2.35, 48.85)
+        );
+    }
+
+    #[test]
+    fn test_gauss_kruger() {
+        let crs = Crs::gauss_kruger_containing(113.97, ZoneWidth::Three);
+        assert_eq!(crs, Crs::gauss_kruger_3(38));
+        assert_eq!(crs.epsg(), Some(4526));
+        assert_eq!(
+            Crs::gauss_kruger_containing(113.97, ZoneWidth::Six),
+            Crs::gauss_kruger_6(19)
+        );
+        assert_eq!(Crs::gauss_kruger_6(19).epsg(), Some(4497));
+
+        // 中央经线与赤道交点
+        assert_close(
+            Crs::Cgcs2000.transform(crs, (114.0, 0.0)),
+            (38_500_000.0, 0.0),
+            1e-6,
+        );
+        // 中央经线上北坐标等于子午线弧长（纬度 30° 约 3 320 113 米）
+        let (x, y) = Crs::Cgcs2000.transform(crs, (114.0, 30.0));
+        assert_close((x, y), (38_500_000.0, 3_320_113.4), 0.5);
+
+        let projected = Crs::Cgcs2000.transform(crs, (113.97, 34.72));
+        assert!((projected.0 - 38_497_254.0).abs() < 5.0);
+        assert_close(
+            crs.transform(Crs::Cgcs2000, projected),
+            (113.97, 34.72),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn test_parse_crs() {
+        assert_eq!("CGCS2000".parse::<Crs>().unwrap(), Crs::Cgcs2000);
+        assert_eq!("gcj-02".parse::<Crs>().unwrap(), Crs::Gcj02);
+        assert_eq!("BD09".parse::<Crs>().unwrap(), Crs::Bd09);
+        assert_eq!("gk3:38".parse::<Crs>().unwrap(), Crs::gauss_kruger_3(38));
+        assert_eq!("EPSG:4526".parse::<Crs>().unwrap(), Crs::gauss_kruger_3(38));
+        assert_eq!("epsg:4497".parse::<Crs>().unwrap(), Crs::gauss_kruger_6(19));
+        assert!("mercator".parse::<Crs>().is_err());
+        assert!("gk3:x".parse::<Crs>().is_err());
+        assert!("gk3:0".parse::<Crs>().is_err());
+        assert!("gk3:255".parse::<Crs>().is_err());
+        assert!("gk6:24".parse::<Crs>().is_err());
+        assert_eq!("gk6:13".parse::<Crs>().unwrap(), Crs::gauss_kruger_6(13));
+    }
+
+    #[test]
+    fn test_transform_geometry_keeps_elevation() {
+        let mut geometry: Geometry =
+            serde_json::from_str(r#"{"type":"Point","coordinates":[116.404,39.915,88.0]}"#)
+                .unwrap();
+        Crs::Cgcs2000.transform_geometry(Crs::Gcj02, &mut geometry);
+        let position = geometry.positions()[0].clone();
+        assert_close(
+            (position[0], position[1]),
+            (116.410_244_499_169_38, 39.916_404_281_501_64),
+            1e-9,
+        );
+        assert_eq!(position[2], 88.0);
+    }
+}
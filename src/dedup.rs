@@ -0,0 +1,202 @@
+//! # 去重
+//!
+//! 重试和重新采集可能把同一地名多次写入导出的 CSV 文件。[`dedup_csv`] 按 `id`
+//! 去重，保留最后一次写入的版本（最近一次获取的结果），并保持首次出现的顺序；
+//! 同一 `id` 的多个版本内容不同时记为冲突，列出不一致的字段供人工核对。
+
+use crate::error::{Error, Result};
+use crate::export::CSV_HEADER;
+use csv::StringRecord;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 同一 ID 内容不同的多个版本
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// 数据 ID
+    pub id: String,
+    /// 内容不一致的列
+    pub fields: Vec<&'static str>,
+    /// 各个不同的版本，按出现顺序排列，最后一个为保留的版本
+    pub versions: Vec<StringRecord>,
+}
+
+/// 去重结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DedupReport {
+    /// 输入的记录数
+    pub total: usize,
+    /// 去重后的记录数
+    pub unique: usize,
+    /// 与已有记录完全相同而被删除的记录数
+    pub identical: usize,
+    /// 内容不同的重复记录
+    pub conflicts: Vec<Conflict>,
+}
+
+impl DedupReport {
+    /// 被删除的记录数
+    pub fn removed(&self) -> usize {
+        self.total - self.unique
+    }
+}
+
+/// 对导出的 CSV 文件按 `id` 去重，写入 `output`
+///
+/// 输入文件的表头必须与 [`CSV_HEADER`] 一致。
+pub fn dedup_csv(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<DedupReport> {
+    let input = input.as_ref();
+    let mut reader = csv::Reader::from_path(input)?;
+    if reader.headers()?.iter().ne(CSV_HEADER) {
+        return Err(Error::Config(format!(
+            "{} 的表头与导出格式不一致",
+            input.display()
+        )));
+    }
+
+    let mut report = DedupReport::default();
+    let mut order: Vec<String> = Vec::new();
+    // id -> 各个不同的版本
+    let mut versions: HashMap<String, Vec<StringRecord>> = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        report.total += 1;
+        let id = record.get(0).unwrap_or_default().to_string();
+        match versions.get_mut(&id) {
+            None => {
+                order.push(id.clone());
+                versions.insert(id, vec![record]);
+            }
+            Some(existing) => {
+                if let Some(i) = existing.iter().position(|v| *v == record) {
+                    // 与之前的版本相同，移到最后作为保留的版本
+                    let same = existing.remove(i);
+                    existing.push(same);
+                    report.identical += 1;
+                } else {
+                    existing.push(record);
+                }
+            }
+        }
+    }
+
+    let mut writer = csv::Writer::from_path(output)?;
+    writer.write_record(CSV_HEADER)?;
+    for id in order {
+        let versions = versions.remove(&id).unwrap_or_default();
+        if let Some(latest) = versions.last() {
+            writer.write_record(latest)?;
+            report.unique += 1;
+        }
+        if versions.len() > 1 {
+            report.conflicts.push(Conflict {
+                fields: differing_fields(&versions),
+                id,
+                versions,
+            });
+        }
+    }
+    writer.flush()?;
+    Ok(report)
+}
+
+/// 把冲突写成 CSV，每行一个版本的一个不一致字段：`id, field, version, value`
+pub fn write_conflicts(report: &DedupReport, path: impl AsRef<Path>) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["id", "field", "version", "value"])?;
+    for conflict in &report.conflicts {
+        for field in &conflict.fields {
+            let index = column_index(field);
+            for (version, record) in conflict.versions.iter().enumerate() {
+                writer.write_record([
+                    conflict.id.as_str(),
+                    field,
+                    &(version + 1).to_string(),
+                    record.get(index).unwrap_or_default(),
+                ])?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn column_index(field: &str) -> usize {
+    CSV_HEADER
+        .iter()
+        .position(|h| *h == field)
+        .unwrap_or_default()
+}
+
+/// 各版本之间取值不同的列
+fn differing_fields(versions: &[StringRecord]) -> Vec<&'static str> {
+    CSV_HEADER
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            let first = versions[0].get(*i);
+            versions[1..].iter().any(|v| v.get(*i) != first)
+        })
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::CsvExporter;
+    use crate::export::test_support::{sample_details, sample_details_with};
+
+    #[test]
+    fn test_dedup_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("all_settlements.csv");
+        let output = dir.path().join("all_settlements_dedup.csv");
+        let mut exporter = CsvExporter::create(&input).unwrap();
+        let other = sample_details_with("def", r#"{"type":"Point","coordinates":[1,2]}"#);
+        let mut updated = sample_details();
+        updated.place_meaning = "更新后的含义".to_string();
+        for details in [
+            &sample_details(),
+            &other,
+            &sample_details(),
+            &updated,
+            &other,
+        ] {
+            exporter.write(details).unwrap();
+        }
+        exporter.flush().unwrap();
+        drop(exporter);
+
+        let report = dedup_csv(&input, &output).unwrap();
+        assert_eq!(
+            (
+                report.total,
+                report.unique,
+                report.identical,
+                report.removed()
+            ),
+            (5, 2, 2, 3)
+        );
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.id, "abc");
+        assert_eq!(conflict.fields, ["place_meaning"]);
+        assert_eq!(conflict.versions.len(), 2);
+
+        // 保留最后写入的版本，顺序与首次出现一致
+        let mut reader = csv::Reader::from_path(&output).unwrap();
+        let rows: Vec<StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((&rows[0][0], &rows[0][14]), ("abc", "更新后的含义"));
+        assert_eq!(&rows[1][0], "def");
+
+        let conflicts = dir.path().join("conflicts.csv");
+        write_conflicts(&report, &conflicts).unwrap();
+        let text = std::fs::read_to_string(&conflicts).unwrap();
+        assert_eq!(
+            text,
+            "id,field,version,value\nabc,place_meaning,1,因唐姓聚居得名\nabc,place_meaning,2,更新后的含义\n"
+        );
+    }
+}
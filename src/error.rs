@@ -0,0 +1,141 @@
+//! # 错误类型
+//!
+//! 区分网络传输错误、HTTP 状态错误、JSON 解析错误以及接口返回的业务错误，
+//! 便于调用方判断 "没有数据" 与 "请求被服务器拒绝" 等不同情况。
+
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// 错误信息中保留的响应内容最大字符数
+const BODY_SNIPPET_LEN: usize = 512;
+
+/// 错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// 网络传输错误（连接失败、超时、读取响应失败等）
+    #[error("网络请求失败: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// 服务器返回非 2xx 状态码
+    #[error("服务器返回错误状态 {status}: {body}")]
+    Status {
+        /// HTTP 状态码
+        status: StatusCode,
+        /// 响应内容片段
+        body: String,
+        /// 响应头 `Retry-After` 指定的等待时间
+        retry_after: Option<Duration>,
+    },
+    /// 响应内容无法解析为预期的 JSON 结构
+    #[error("响应解析失败: {source}")]
+    Decode {
+        /// 解析错误
+        #[source]
+        source: serde_json::Error,
+        /// 原始响应内容
+        body: String,
+    },
+    /// 文件读写错误（缓存、请求日志、导出文件等）
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    /// CSV 读写错误
+    #[error("CSV 读写失败: {0}")]
+    Csv(#[from] csv::Error),
+    /// SQLite 读写错误（GeoPackage 等）
+    #[error("SQLite 读写失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// Parquet 读写错误
+    #[error("Parquet 读写失败: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    /// 客户端配置无效（服务地址、代理等）
+    #[error("客户端配置错误: {0}")]
+    Config(String),
+    /// 超出每日请求配额
+    #[error("已超出每日请求配额 ({limit} 次)")]
+    QuotaExceeded {
+        /// 每日请求上限
+        limit: u64,
+    },
+    /// 离线模式下缓存中没有对应的响应
+    #[error("缓存未命中 ({endpoint}): {key}")]
+    CacheMiss {
+        /// 接口名称
+        endpoint: &'static str,
+        /// 规范化后的请求参数
+        key: String,
+    },
+    /// 接口返回的业务错误
+    #[error("接口返回错误 (status: {status}): {}", message.as_deref().unwrap_or("无错误信息"))]
+    Api {
+        /// 服务状态码
+        status: i32,
+        /// 服务信息
+        message: Option<String>,
+    },
+}
+
+impl Error {
+    /// 根据状态码和完整响应内容构造 HTTP 状态错误，响应内容会被截断
+    pub(crate) fn status(status: StatusCode, body: &str, retry_after: Option<Duration>) -> Self {
+        Self::Status {
+            status,
+            body: snippet(body),
+            retry_after,
+        }
+    }
+
+    /// HTTP 状态码（仅 [`Error::Status`] 及带状态码的传输错误）
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            Self::Transport(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// 服务器要求的重试等待时间（仅 [`Error::Status`]）
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// 截取响应内容的前 [`BODY_SNIPPET_LEN`] 个字符
+fn snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_LEN) {
+        Some((idx, _)) => format!("{}...", &body[..idx]),
+        None => body.to_string(),
+    }
+}
+
+/// 地名服务客户端结果类型
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_error_truncates_body() {
+        let body = "错".repeat(BODY_SNIPPET_LEN + 10);
+        let err = Error::status(StatusCode::FORBIDDEN, &body, None);
+        match &err {
+            Error::Status { status, body, .. } => {
+                assert_eq!(*status, StatusCode::FORBIDDEN);
+                assert_eq!(body.chars().count(), BODY_SNIPPET_LEN + 3);
+            }
+            other => panic!("错误类型不正确: {:?}", other),
+        }
+        assert_eq!(err.status_code(), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn test_api_error_display() {
+        let err = Error::Api {
+            status: 500,
+            message: Some("服务异常".to_string()),
+        };
+        assert_eq!(err.to_string(), "接口返回错误 (status: 500): 服务异常");
+    }
+}
@@ -0,0 +1,296 @@
+//! # CSV 导出
+//!
+//! 地名详情中的 `gdm` 是嵌套的 GeoJSON 对象，无法直接按列写入 CSV。
+//! [`FlatRecord`] 把它展开为 `lon`、`lat`、`geometry_type` 和 `wkt` 四列，
+//! 列顺序固定为 [`CSV_HEADER`]，并总是显式写出表头。
+//! [`CsvExporter::append`] 可以在中断的采集之后继续追加到已有文件。
+
+use super::reproject;
+use crate::api::DetailsQueryResponse;
+use crate::crs::Crs;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// CSV 表头，与 [`FlatRecord`] 的字段顺序一致
+pub const CSV_HEADER: [&str; 21] = [
+    "id",
+    "standard_name",
+    "roman_alphabet_spelling",
+    "place_type",
+    "place_type_code",
+    "place_code",
+    "province",
+    "province_name",
+    "city",
+    "city_name",
+    "area",
+    "area_name",
+    "old_name",
+    "ethnic_minorities_writing",
+    "place_meaning",
+    "place_origin",
+    "government_history",
+    "lon",
+    "lat",
+    "geometry_type",
+    "wkt",
+];
+
+/// 扁平化的地名详情，每个字段对应 CSV 的一列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatRecord {
+    /// 数据 ID
+    pub id: String,
+    /// 标准名称
+    pub standard_name: String,
+    /// 罗马字母拼写
+    pub roman_alphabet_spelling: String,
+    /// 地名类别
+    pub place_type: String,
+    /// 地名类别代码
+    pub place_type_code: String,
+    /// 地名代码
+    pub place_code: String,
+    /// 省级行政代码
+    pub province: String,
+    /// 省级政区名称
+    pub province_name: String,
+    /// 市级行政代码
+    pub city: Option<String>,
+    /// 所在地市名称
+    pub city_name: Option<String>,
+    /// 区县级行政代码
+    pub area: Option<String>,
+    /// 所在区县名称
+    pub area_name: Option<String>,
+    /// 历史地名
+    pub old_name: Option<String>,
+    /// 少数民族语书写
+    pub ethnic_minorities_writing: String,
+    /// 地名的含义
+    pub place_meaning: String,
+    /// 地名的来历
+    pub place_origin: String,
+    /// 政区的历史沿革
+    pub government_history: Option<String>,
+    /// 经度（几何中心点）
+    pub lon: Option<f64>,
+    /// 纬度（几何中心点）
+    pub lat: Option<f64>,
+    /// 几何类型
+    pub geometry_type: String,
+    /// WKT 格式的几何对象
+    pub wkt: String,
+}
+
+impl From<&DetailsQueryResponse> for FlatRecord {
+    fn from(details: &DetailsQueryResponse) -> Self {
+        let center = details.gdm.centroid();
+        Self {
+            id: details.id.clone(),
+            standard_name: details.standard_name.clone(),
+            roman_alphabet_spelling: details.roman_alphabet_spelling.clone(),
+            place_type: details.place_type.clone(),
+            place_type_code: details.place_type_code.clone(),
+            place_code: details.place_code.clone(),
+            province: details.province.clone(),
+            province_name: details.province_name.clone(),
+            city: details.city.clone(),
+            city_name: details.city_name.clone(),
+            area: details.area.clone(),
+            area_name: details.area_name.clone(),
+            old_name: details.old_name.clone(),
+            ethnic_minorities_writing: details.ethnic_minorities_writing.clone(),
+            place_meaning: details.place_meaning.clone(),
+            place_origin: details.place_origin.clone(),
+            government_history: details.government_history.clone(),
+            lon: center.map(|(lon, _)| lon),
+            lat: center.map(|(_, lat)| lat),
+            geometry_type: details.gdm.type_name().to_string(),
+            wkt: details.gdm.to_wkt(),
+        }
+    }
+}
+
+/// CSV 导出器
+pub struct CsvExporter<W: Write> {
+    writer: ::csv::Writer<W>,
+    crs: Crs,
+}
+
+impl CsvExporter<File> {
+    /// 创建（或清空）CSV 文件并写入表头
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_writer(File::create(path)?)
+    }
+
+    /// 追加到已有的 CSV 文件，文件不存在或为空时写入表头
+    ///
+    /// 已有文件的表头必须与 [`CSV_HEADER`] 一致；中断时没有写完的最后一行会被截掉。
+    pub fn append(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::create(path),
+            Err(e) => return Err(e.into()),
+        };
+        if file.metadata()?.len() == 0 {
+            return Self::create(path);
+        }
+        let end = complete_records_end(&file)?.ok_or_else(|| {
+            crate::Error::Config(format!("{} 的表头与导出格式不一致", path.display()))
+        })?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        file.set_len(end)?;
+        Ok(Self {
+            writer: ::csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(file),
+            crs: Crs::default(),
+        })
+    }
+}
+
+/// 最后一条完整记录结束的位置；表头不一致时返回 `None`
+///
+/// 逐条读取记录，不把整个文件读入内存
+fn complete_records_end(mut file: &File) -> Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    let mut last = [0u8];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
+    let mut record = ::csv::ByteRecord::new();
+    if !matches!(reader.read_byte_record(&mut record), Ok(true))
+        || record.iter().ne(CSV_HEADER.iter().map(|h| h.as_bytes()))
+    {
+        return Ok(None);
+    }
+    let mut end = reader.position().byte();
+    while let Ok(true) = reader.read_byte_record(&mut record) {
+        let next = reader.position().byte();
+        // 字段数不足或文件末尾缺少换行符说明这一行没有写完
+        if record.len() != CSV_HEADER.len() || (next == len && last[0] != b'\n') {
+            break;
+        }
+        end = next;
+    }
+    Ok(Some(end))
+}
+
+impl<W: Write> CsvExporter<W> {
+    /// 包装任意输出并写入表头
+    pub fn from_writer(writer: W) -> Result<Self> {
+        let mut writer = ::csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        writer.write_record(CSV_HEADER)?;
+        Ok(Self {
+            writer,
+            crs: Crs::default(),
+        })
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；投影坐标系下 `lon`、`lat` 列为东、北坐标
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// 写入一条地名详情
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        self.write_record(&FlatRecord::from(reproject(details, self.crs).as_ref()))
+    }
+
+    /// 写入一条扁平化记录
+    pub fn write_record(&mut self, record: &FlatRecord) -> Result<()> {
+        self.writer.serialize(record)?;
+        Ok(())
+    }
+
+    /// 把缓冲区写入底层输出
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// 刷新并取回底层输出
+    pub fn into_inner(self) -> Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|e| crate::Error::Io(e.into_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, write_partial};
+
+    #[test]
+    fn test_csv_header_and_columns() {
+        let mut exporter = CsvExporter::from_writer(Vec::new()).unwrap();
+        exporter.write(&sample_details()).unwrap();
+        let output = String::from_utf8(exporter.into_inner().unwrap()).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next().unwrap(), CSV_HEADER.join(","));
+
+        let mut reader = ::csv::Reader::from_reader(output.as_bytes());
+        let record: FlatRecord = reader.deserialize().next().unwrap().unwrap();
+        assert_eq!(record.standard_name, "唐庄村");
+        assert_eq!(record.lon, Some(113.97));
+        assert_eq!(record.lat, Some(34.72));
+        assert_eq!(record.geometry_type, "Point");
+        assert_eq!(record.wkt, "POINT (113.97 34.72)");
+        assert_eq!(record.place_origin, "明代建村, 名为\"唐庄\"");
+    }
+
+    #[test]
+    fn test_projected_columns() {
+        let crs = Crs::gauss_kruger_3(38);
+        let mut exporter = CsvExporter::from_writer(Vec::new()).unwrap().crs(crs);
+        exporter.write(&sample_details()).unwrap();
+        let output = exporter.into_inner().unwrap();
+
+        let mut reader = ::csv::Reader::from_reader(output.as_slice());
+        let record: FlatRecord = reader.deserialize().next().unwrap().unwrap();
+        let (x, y) = Crs::Cgcs2000.transform(crs, (113.97, 34.72));
+        assert_eq!((record.lon, record.lat), (Some(x), Some(y)));
+        assert!(record.wkt.starts_with("POINT (38497"));
+    }
+
+    #[test]
+    fn test_append_truncates_partial_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.csv");
+        let mut exporter = CsvExporter::append(&path).unwrap();
+        exporter.write(&sample_details()).unwrap();
+        exporter.flush().unwrap();
+        drop(exporter);
+        write_partial(&path, "def,李庄村,\"Lǐzhuāng".as_bytes());
+
+        let mut exporter = CsvExporter::append(&path).unwrap();
+        let mut other = sample_details();
+        other.id = "ghi".to_string();
+        exporter.write(&other).unwrap();
+        exporter.flush().unwrap();
+
+        let mut reader = ::csv::Reader::from_path(&path).unwrap();
+        let ids: Vec<String> = reader
+            .deserialize::<FlatRecord>()
+            .map(|r| r.unwrap().id)
+            .collect();
+        assert_eq!(ids, ["abc", "ghi"]);
+
+        std::fs::write(&path, "a,b\n").unwrap();
+        assert!(CsvExporter::append(&path).is_err());
+    }
+}
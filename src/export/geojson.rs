@@ -0,0 +1,447 @@
+//! # GeoJSON 导出
+//!
+//! 把地名详情或搜索结果写成 GeoJSON `FeatureCollection`。
+//! [`GeoJsonWriter`] 逐条写出要素，不需要把全部数据放在内存中，
+//! 写完后必须调用 [`GeoJsonWriter::finish`] 补全文件结尾。
+//! [`GeoJsonWriter::append`] 可以在中断的采集之后继续追加到已有文件。
+
+use super::require_geographic;
+use crate::api::{DetailsQueryResponse, Geometry, Record};
+use crate::crs::Crs;
+use crate::error::Result;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// `FeatureCollection` 开头
+const COLLECTION_START: &str = r#"{"type":"FeatureCollection","features":["#;
+
+/// 可以转换为 GeoJSON 要素的数据
+pub trait ToFeature {
+    /// 生成要素
+    fn to_feature(&self) -> Feature<'_>;
+}
+
+/// GeoJSON 要素
+#[derive(Debug, Clone, Serialize)]
+pub struct Feature<'a> {
+    r#type: &'static str,
+    /// 要素 ID，即地名数据 ID
+    pub id: &'a str,
+    /// 几何对象
+    pub geometry: Option<&'a Geometry>,
+    /// 属性
+    pub properties: FeatureProperties<'a>,
+}
+
+/// GeoJSON 要素属性，缺失的字段不输出
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeatureProperties<'a> {
+    /// 标准名称
+    pub standard_name: &'a str,
+    /// 罗马字母拼写（拼音）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roman_alphabet_spelling: Option<&'a str>,
+    /// 地名类别
+    pub place_type: &'a str,
+    /// 地名类别代码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place_type_code: Option<&'a str>,
+    /// 地名代码
+    pub place_code: &'a str,
+    /// 省级行政代码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub province: Option<&'a str>,
+    /// 省级政区名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub province_name: Option<&'a str>,
+    /// 市级行政代码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<&'a str>,
+    /// 市级政区名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city_name: Option<&'a str>,
+    /// 区县级行政代码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub area: Option<&'a str>,
+    /// 区县级政区名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub area_name: Option<&'a str>,
+    /// 历史地名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_name: Option<&'a str>,
+    /// 地名的含义
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place_meaning: Option<&'a str>,
+    /// 地名的来历
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place_origin: Option<&'a str>,
+    /// 政区的历史沿革
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub government_history: Option<&'a str>,
+}
+
+impl<'a> Feature<'a> {
+    /// 由 ID、几何对象和属性创建要素
+    pub fn new(
+        id: &'a str,
+        geometry: Option<&'a Geometry>,
+        properties: FeatureProperties<'a>,
+    ) -> Self {
+        Self {
+            r#type: "Feature",
+            id,
+            geometry,
+            properties,
+        }
+    }
+}
+
+impl ToFeature for DetailsQueryResponse {
+    fn to_feature(&self) -> Feature<'_> {
+        let properties = FeatureProperties {
+            standard_name: &self.standard_name,
+            roman_alphabet_spelling: Some(&self.roman_alphabet_spelling),
+            place_type: &self.place_type,
+            place_type_code: Some(&self.place_type_code),
+            place_code: &self.place_code,
+            province: Some(&self.province),
+            province_name: Some(&self.province_name),
+            city: self.city.as_deref(),
+            city_name: self.city_name.as_deref(),
+            area: self.area.as_deref(),
+            area_name: self.area_name.as_deref(),
+            old_name: self.old_name.as_deref(),
+            place_meaning: Some(&self.place_meaning),
+            place_origin: Some(&self.place_origin),
+            government_history: self.government_history.as_deref(),
+        };
+        Feature::new(&self.id, Some(&self.gdm), properties)
+    }
+}
+
+impl ToFeature for Record {
+    fn to_feature(&self) -> Feature<'_> {
+        let properties = FeatureProperties {
+            standard_name: &self.standard_name,
+            roman_alphabet_spelling: Some(&self.roman_alphabet_spelling),
+            place_type: &self.place_type,
+            place_type_code: self.place_type_code.as_deref(),
+            place_code: &self.place_code,
+            province: self.province.as_deref(),
+            province_name: self.province_name.as_deref(),
+            city: self.city.as_deref(),
+            city_name: self.city_name.as_deref(),
+            area: self.area.as_deref(),
+            area_name: self.area_name.as_deref(),
+            ..Default::default()
+        };
+        Feature::new(&self.id, self.gdm.as_ref(), properties)
+    }
+}
+
+/// 流式 GeoJSON `FeatureCollection` 写入器，每个要素占一行
+pub struct GeoJsonWriter<W: Write> {
+    writer: W,
+    count: usize,
+    crs: Crs,
+}
+
+impl GeoJsonWriter<BufWriter<File>> {
+    /// 创建（或清空）GeoJSON 文件
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    /// 追加到由 [`GeoJsonWriter`] 写出的已有文件，文件不存在或为空时新建
+    ///
+    /// 已补全的文件结尾和中断时没有写完的最后一个要素会被截掉，之后的要素接着写入。
+    pub fn append(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::create(path),
+            Err(e) => return Err(e.into()),
+        };
+        if file.metadata()?.len() == 0 {
+            return Self::create(path);
+        }
+        let Some((end, count)) = complete_features_end(file)? else {
+            return Err(crate::Error::Config(format!(
+                "{} 不是由本程序写出的 GeoJSON 文件",
+                path.display()
+            )));
+        };
+        let file = OpenOptions::new().append(true).open(path)?;
+        file.set_len(end)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            count,
+            crs: Crs::default(),
+        })
+    }
+}
+
+/// 最后一个完整要素结束的位置和完整要素数；不是由 [`GeoJsonWriter`] 写出的文件返回 `None`
+///
+/// 按括号层级逐字节扫描，内存中只保留当前要素，不依赖要素的换行方式
+fn complete_features_end(file: File) -> Result<Option<(u64, usize)>> {
+    let mut bytes = BufReader::new(file).bytes();
+    let start = bytes
+        .by_ref()
+        .take(COLLECTION_START.len())
+        .collect::<std::io::Result<Vec<u8>>>()?;
+    if start != COLLECTION_START.as_bytes() {
+        return Ok(None);
+    }
+    let mut offset = start.len() as u64;
+    let mut end = offset;
+    let mut count = 0;
+    let mut feature = Vec::new();
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for byte in bytes {
+        let byte = byte?;
+        offset += 1;
+        if depth == 0 {
+            match byte {
+                b'{' => {}
+                b',' | b' ' | b'\t' | b'\r' | b'\n' => continue,
+                // 已补全的文件结尾
+                _ => break,
+            }
+        }
+        feature.push(byte);
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    // 损坏的要素无法解析，从这里截断
+                    let complete = serde_json::from_slice::<serde_json::Value>(&feature)
+                        .is_ok_and(|v| v.is_object());
+                    if !complete {
+                        break;
+                    }
+                    end = offset;
+                    count += 1;
+                    feature.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(Some((end, count)))
+}
+
+impl<W: Write> GeoJsonWriter<W> {
+    /// 写出 `FeatureCollection` 开头
+    pub fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(COLLECTION_START.as_bytes())?;
+        Ok(Self {
+            writer,
+            count: 0,
+            crs: Crs::default(),
+        })
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；网络地图可使用 GCJ-02 或 BD-09
+    ///
+    /// GeoJSON（RFC 7946）只能使用经纬度坐标，投影坐标系返回 [`Error::Config`](crate::Error::Config)
+    pub fn crs(mut self, crs: Crs) -> Result<Self> {
+        require_geographic(crs, "GeoJSON")?;
+        self.crs = crs;
+        Ok(self)
+    }
+
+    /// 写入一个要素
+    pub fn write<T: ToFeature + ?Sized>(&mut self, item: &T) -> Result<()> {
+        let mut feature = item.to_feature();
+        if self.crs == Crs::default() {
+            return self.write_feature(&feature);
+        }
+        let mut geometry = feature.geometry.cloned();
+        if let Some(geometry) = &mut geometry {
+            Crs::default().transform_geometry(self.crs, geometry);
+        }
+        feature.geometry = geometry.as_ref();
+        self.write_feature(&feature)
+    }
+
+    /// 写入一个已构造的要素
+    pub fn write_feature(&mut self, feature: &Feature<'_>) -> Result<()> {
+        let separator: &[u8] = if self.count == 0 { b"\n" } else { b",\n" };
+        self.writer.write_all(separator)?;
+        serde_json::to_writer(&mut self.writer, feature).map_err(std::io::Error::from)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已写入的要素数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 把缓冲区写入底层输出
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// 写出 `FeatureCollection` 结尾并取回底层输出
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.write_all(b"\n]}\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with, write_partial};
+
+    #[test]
+    fn test_feature_collection() {
+        let mut writer = GeoJsonWriter::new(Vec::new()).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer
+            .write(&sample_details_with(
+                "def",
+                r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#,
+            ))
+            .unwrap();
+        assert_eq!(writer.count(), 2);
+        let output = writer.finish().unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["type"], "FeatureCollection");
+        let features = value["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["type"], "Feature");
+        assert_eq!(features[0]["id"], "abc");
+        assert_eq!(features[0]["geometry"]["type"], "Point");
+        assert_eq!(features[0]["properties"]["standard_name"], "唐庄村");
+        assert_eq!(
+            features[0]["properties"]["roman_alphabet_spelling"],
+            "Tángzhuāng Cūn"
+        );
+        assert_eq!(features[0]["properties"]["place_meaning"], "因唐姓聚居得名");
+        assert!(features[0]["properties"].get("old_name").is_none());
+        assert_eq!(features[1]["geometry"]["type"], "Polygon");
+    }
+
+    #[test]
+    fn test_projected_crs_rejected() {
+        let writer = GeoJsonWriter::new(Vec::new()).unwrap();
+        assert!(writer.crs(Crs::gauss_kruger_3(38)).is_err());
+        let writer = GeoJsonWriter::new(Vec::new()).unwrap();
+        assert!(writer.crs(Crs::Gcj02).is_ok());
+    }
+
+    #[test]
+    fn test_search_record_feature() {
+        let record: Record = serde_json::from_str(
+            r#"{"id":"abc","place_code":"410122000000","standard_name":"唐庄村","roman_alphabet_spelling":"Tángzhuāng Cūn","place_type":"农村居民点","place_type_code":"22200","province_name":"河南省","city_name":"郑州市","area_name":"中牟县","area":"410122000000","city":"410100000000","province":"410000000000","gdm":null}"#,
+        )
+        .unwrap();
+        let value = serde_json::to_value(record.to_feature()).unwrap();
+        assert_eq!(value["id"], "abc");
+        assert!(value["geometry"].is_null());
+        assert_eq!(value["properties"]["area_name"], "中牟县");
+        assert!(value["properties"].get("place_meaning").is_none());
+    }
+
+    #[test]
+    fn test_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.geojson");
+        let mut writer = GeoJsonWriter::append(&path).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.finish().unwrap();
+
+        // 追加到已补全结尾的文件
+        let mut writer = GeoJsonWriter::append(&path).unwrap();
+        assert_eq!(writer.count(), 1);
+        writer
+            .write(&sample_details_with(
+                "def",
+                r#"{"type":"Point","coordinates":[1,2]}"#,
+            ))
+            .unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        write_partial(
+            &path,
+            br#",
+{"type":"Feature","id":"gh"#,
+        );
+
+        let mut writer = GeoJsonWriter::append(&path).unwrap();
+        assert_eq!(writer.count(), 2);
+        writer
+            .write(&sample_details_with(
+                "ghi",
+                r#"{"type":"Point","coordinates":[3,4]}"#,
+            ))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        let ids: Vec<_> = value["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["abc", "def", "ghi"]);
+    }
+
+    #[test]
+    fn test_append_multiline_features() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.geojson");
+        // 要素跨越多行，字符串中含有括号和转义的引号
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n{{\n  \"type\": \"Feature\",\n  \"id\": \"a}}\\\"b\",\n  \"geometry\": null\n}}\n]}}",
+                COLLECTION_START
+            ),
+        )
+        .unwrap();
+
+        let mut writer = GeoJsonWriter::append(&path).unwrap();
+        assert_eq!(writer.count(), 1);
+        writer.write(&sample_details()).unwrap();
+        writer.finish().unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        let ids: Vec<_> = value["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["a}\"b", "abc"]);
+    }
+
+    #[test]
+    fn test_empty_collection() {
+        let output = GeoJsonWriter::new(Vec::new()).unwrap().finish().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(value["features"].as_array().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,606 @@
+//! # GeoPackage 导出
+//!
+//! 把地名详情写入单个 `.gpkg` 文件（OGC GeoPackage 1.2，基于 SQLite），
+//! 可直接在 QGIS 中打开。每个地名类别一张要素表，表名为 `place_<类别代码>`，
+//! 几何列 `geom` 默认使用 CGCS2000 经纬度坐标（EPSG:4490）并带有 R-tree 空间索引，
+//! 属性列与 [`FlatRecord`] 一致（几何类型和 WKT 除外）。
+
+use super::csv::FlatRecord;
+use super::reproject;
+use crate::api::{DetailsQueryResponse, Geometry};
+use crate::crs::Crs;
+use crate::error::Result;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// CGCS2000 经纬度坐标系的 SRS ID
+pub const CGCS2000_SRS_ID: i32 = 4490;
+
+/// 坐标系在 GeoPackage 中的 SRS ID：有 EPSG 代码时使用 EPSG 代码，否则使用自定义编号
+pub fn srs_id(crs: Crs) -> i32 {
+    match (crs.epsg(), crs) {
+        (Some(code), _) => code as i32,
+        (None, Crs::Gcj02) => 100_002,
+        (None, Crs::Bd09) => 100_009,
+        (None, Crs::GaussKruger { width, zone }) => {
+            200_000 + i32::from(width.degrees()) * 1000 + i32::from(zone)
+        }
+        (None, _) => 0,
+    }
+}
+
+/// GeoPackage 的 `application_id`（"GPKG"）
+const APPLICATION_ID: i32 = 1196444487;
+
+/// GeoPackage 必需的元数据表
+const SCHEMA: &str = r#"
+PRAGMA application_id = 1196444487;
+PRAGMA user_version = 10200;
+CREATE TABLE gpkg_spatial_ref_sys (
+    srs_name TEXT NOT NULL,
+    srs_id INTEGER NOT NULL PRIMARY KEY,
+    organization TEXT NOT NULL,
+    organization_coordsys_id INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    description TEXT
+);
+CREATE TABLE gpkg_contents (
+    table_name TEXT NOT NULL PRIMARY KEY,
+    data_type TEXT NOT NULL,
+    identifier TEXT UNIQUE,
+    description TEXT DEFAULT '',
+    last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    min_x DOUBLE,
+    min_y DOUBLE,
+    max_x DOUBLE,
+    max_y DOUBLE,
+    srs_id INTEGER,
+    CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+CREATE TABLE gpkg_geometry_columns (
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    geometry_type_name TEXT NOT NULL,
+    srs_id INTEGER NOT NULL,
+    z TINYINT NOT NULL,
+    m TINYINT NOT NULL,
+    CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+    CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+    CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+    CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+CREATE TABLE gpkg_extensions (
+    table_name TEXT,
+    column_name TEXT,
+    extension_name TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    CONSTRAINT ge_tce UNIQUE (table_name, column_name, extension_name)
+);
+INSERT INTO gpkg_spatial_ref_sys VALUES
+    ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', 'undefined cartesian coordinate reference system'),
+    ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', 'undefined geographic coordinate reference system');
+"#;
+
+/// 要素表的属性列，与 [`FlatRecord`] 的字段对应
+const ATTRIBUTE_COLUMNS: [(&str, &str); 19] = [
+    ("id", "TEXT NOT NULL UNIQUE"),
+    ("standard_name", "TEXT NOT NULL"),
+    ("roman_alphabet_spelling", "TEXT"),
+    ("place_type", "TEXT"),
+    ("place_type_code", "TEXT"),
+    ("place_code", "TEXT"),
+    ("province", "TEXT"),
+    ("province_name", "TEXT"),
+    ("city", "TEXT"),
+    ("city_name", "TEXT"),
+    ("area", "TEXT"),
+    ("area_name", "TEXT"),
+    ("old_name", "TEXT"),
+    ("ethnic_minorities_writing", "TEXT"),
+    ("place_meaning", "TEXT"),
+    ("place_origin", "TEXT"),
+    ("government_history", "TEXT"),
+    ("lon", "DOUBLE"),
+    ("lat", "DOUBLE"),
+];
+
+/// 地名类别对应的要素表名
+pub fn feature_table_name(place_type_code: &str) -> String {
+    let code: String = place_type_code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    if code.is_empty() {
+        "place_unknown".to_string()
+    } else {
+        format!("place_{}", code)
+    }
+}
+
+/// GeoPackage 导出器
+///
+/// 写入在事务中进行，[`flush`](Self::flush) 提交已写入的要素；
+/// 结束时必须调用 [`finish`](Self::finish) 更新各表的范围，否则最后一次提交之后的要素会丢失。
+/// 同一 ID 重复写入时保留第一次写入的要素。
+pub struct GeoPackageWriter {
+    conn: Connection,
+    /// 地名类别代码到要素表名
+    tables: HashMap<String, String>,
+    count: usize,
+    crs: Crs,
+}
+
+impl GeoPackageWriter {
+    /// 创建 GeoPackage 文件，已存在的文件会被覆盖
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        let conn = Connection::open(path)?;
+        register_functions(&conn)?;
+        conn.execute_batch(SCHEMA)?;
+        // 规范要求必须包含 WGS 84
+        register_srs(&conn, Crs::Wgs84)?;
+        register_srs(&conn, Crs::Cgcs2000)?;
+        Self::begin(conn)
+    }
+
+    /// 打开由 [`GeoPackageWriter`] 写出的已有文件继续写入，文件不存在时新建
+    ///
+    /// 已有的要素表沿用创建时的坐标系，输出坐标系应与之一致。
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::create(path);
+        }
+        let conn = Connection::open(path)?;
+        let application_id: i32 =
+            conn.pragma_query_value(None, "application_id", |row| row.get(0))?;
+        if application_id != APPLICATION_ID {
+            return Err(crate::Error::Config(format!(
+                "{} 不是 GeoPackage 文件",
+                path.display()
+            )));
+        }
+        register_functions(&conn)?;
+        Self::begin(conn)
+    }
+
+    fn begin(conn: Connection) -> Result<Self> {
+        conn.execute_batch("BEGIN")?;
+        Ok(Self {
+            conn,
+            tables: HashMap::new(),
+            count: 0,
+            crs: Crs::default(),
+        })
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；需在写入前设置
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// 写入一条地名详情
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        let details = reproject(details, self.crs);
+        let table = self.table_for(&details.place_type_code, &details.place_type)?;
+        let record = FlatRecord::from(details.as_ref());
+        let columns: Vec<&str> = ATTRIBUTE_COLUMNS.iter().map(|(name, _)| *name).collect();
+        let placeholders: Vec<String> =
+            (2..=columns.len() + 1).map(|i| format!("?{}", i)).collect();
+        let sql = format!(
+            r#"INSERT OR IGNORE INTO "{}" (geom, {}) VALUES (?1, {})"#,
+            table,
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let inserted = self.conn.execute(
+            &sql,
+            params![
+                geometry_blob(&details.gdm, srs_id(self.crs)),
+                record.id,
+                record.standard_name,
+                record.roman_alphabet_spelling,
+                record.place_type,
+                record.place_type_code,
+                record.place_code,
+                record.province,
+                record.province_name,
+                record.city,
+                record.city_name,
+                record.area,
+                record.area_name,
+                record.old_name,
+                record.ethnic_minorities_writing,
+                record.place_meaning,
+                record.place_origin,
+                record.government_history,
+                record.lon,
+                record.lat,
+            ],
+        )?;
+        self.count += inserted;
+        Ok(())
+    }
+
+    /// 已写入的要素数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 提交已写入的要素
+    pub fn flush(&mut self) -> Result<()> {
+        self.conn.execute_batch("COMMIT; BEGIN")?;
+        Ok(())
+    }
+
+    /// 更新各要素表的范围并提交
+    pub fn finish(self) -> Result<()> {
+        // 包括之前中断、没有更新范围的要素表
+        let tables: Vec<String> = self
+            .conn
+            .prepare("SELECT table_name FROM gpkg_contents WHERE data_type = 'features'")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        for table in &tables {
+            self.conn.execute(
+                &format!(
+                    r#"UPDATE gpkg_contents SET
+                        min_x = (SELECT min(minx) FROM "rtree_{0}_geom"),
+                        min_y = (SELECT min(miny) FROM "rtree_{0}_geom"),
+                        max_x = (SELECT max(maxx) FROM "rtree_{0}_geom"),
+                        max_y = (SELECT max(maxy) FROM "rtree_{0}_geom"),
+                        last_change = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+                    WHERE table_name = ?1"#,
+                    table
+                ),
+                [table],
+            )?;
+        }
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    /// 取得（必要时创建）地名类别对应的要素表
+    fn table_for(&mut self, place_type_code: &str, place_type: &str) -> Result<String> {
+        if let Some(table) = self.tables.get(place_type_code) {
+            return Ok(table.clone());
+        }
+        let table = feature_table_name(place_type_code);
+        let exists = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM gpkg_contents WHERE table_name = ?1",
+                [&table],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !exists {
+            register_srs(&self.conn, self.crs)?;
+            create_feature_table(
+                &self.conn,
+                &table,
+                place_type_code,
+                place_type,
+                srs_id(self.crs),
+            )?;
+        }
+        self.tables
+            .insert(place_type_code.to_string(), table.clone());
+        Ok(table)
+    }
+}
+
+/// 创建要素表、登记元数据并建立 R-tree 空间索引
+fn create_feature_table(
+    conn: &Connection,
+    table: &str,
+    place_type_code: &str,
+    place_type: &str,
+    srs_id: i32,
+) -> Result<()> {
+    let columns: Vec<String> = ATTRIBUTE_COLUMNS
+        .iter()
+        .map(|(name, kind)| format!("{} {}", name, kind))
+        .collect();
+    conn.execute_batch(&format!(
+        r#"CREATE TABLE "{}" (fid INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, geom GEOMETRY, {})"#,
+        table,
+        columns.join(", ")
+    ))?;
+    let identifier = if place_type.is_empty() {
+        table.to_string()
+    } else {
+        format!("{}（{}）", place_type, place_type_code)
+    };
+    conn.execute(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, srs_id)
+         VALUES (?1, 'features', ?2, ?3, ?4)",
+        params![table, identifier, place_type, srs_id],
+    )?;
+    conn.execute(
+        "INSERT INTO gpkg_geometry_columns VALUES (?1, 'geom', 'GEOMETRY', ?2, 0, 0)",
+        params![table, srs_id],
+    )?;
+    conn.execute(
+        "INSERT INTO gpkg_extensions VALUES (?1, 'geom', 'gpkg_rtree_index',
+         'http://www.geopackage.org/spec120/#extension_rtree', 'write-only')",
+        [table],
+    )?;
+    // 空间索引触发器（GeoPackage 1.2 规范 F.3）
+    conn.execute_batch(&format!(
+        r#"
+CREATE VIRTUAL TABLE "rtree_{t}_geom" USING rtree(id, minx, maxx, miny, maxy);
+CREATE TRIGGER "rtree_{t}_geom_insert" AFTER INSERT ON "{t}"
+WHEN (new.geom NOT NULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+  INSERT OR REPLACE INTO "rtree_{t}_geom" VALUES (NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom));
+END;
+CREATE TRIGGER "rtree_{t}_geom_update1" AFTER UPDATE OF geom ON "{t}"
+WHEN OLD.fid = NEW.fid AND (NEW.geom NOTNULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+  INSERT OR REPLACE INTO "rtree_{t}_geom" VALUES (NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom));
+END;
+CREATE TRIGGER "rtree_{t}_geom_update2" AFTER UPDATE OF geom ON "{t}"
+WHEN OLD.fid = NEW.fid AND (NEW.geom ISNULL OR ST_IsEmpty(NEW.geom))
+BEGIN
+  DELETE FROM "rtree_{t}_geom" WHERE id = OLD.fid;
+END;
+CREATE TRIGGER "rtree_{t}_geom_update3" AFTER UPDATE ON "{t}"
+WHEN OLD.fid != NEW.fid AND (NEW.geom NOTNULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+  DELETE FROM "rtree_{t}_geom" WHERE id = OLD.fid;
+  INSERT OR REPLACE INTO "rtree_{t}_geom" VALUES (NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom));
+END;
+CREATE TRIGGER "rtree_{t}_geom_update4" AFTER UPDATE ON "{t}"
+WHEN OLD.fid != NEW.fid AND (NEW.geom ISNULL OR ST_IsEmpty(NEW.geom))
+BEGIN
+  DELETE FROM "rtree_{t}_geom" WHERE id IN (OLD.fid, NEW.fid);
+END;
+CREATE TRIGGER "rtree_{t}_geom_delete" AFTER DELETE ON "{t}"
+WHEN old.geom NOT NULL
+BEGIN
+  DELETE FROM "rtree_{t}_geom" WHERE id = OLD.fid;
+END;
+"#,
+        t = table
+    ))?;
+    Ok(())
+}
+
+/// 登记坐标系定义（已存在时忽略）
+fn register_srs(conn: &Connection, crs: Crs) -> Result<()> {
+    let id = srs_id(crs);
+    let (organization, description) = match crs.epsg() {
+        Some(_) => ("EPSG", format!("{}", crs)),
+        None => (
+            "NONE",
+            format!("{}（坐标值经过偏移或非标准分带，定义仅供参考）", crs),
+        ),
+    };
+    conn.execute(
+        "INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES (?1, ?2, ?3, ?2, ?4, ?5)",
+        params![crs.to_string(), id, organization, crs.wkt(), description],
+    )?;
+    Ok(())
+}
+
+/// 编码为 GeoPackage 几何二进制：`GP` 头、SRS ID、外包框和 WKB
+pub fn geometry_blob(geometry: &Geometry, srs_id: i32) -> Vec<u8> {
+    let bbox = geometry.bbox();
+    let mut blob = b"GP\0".to_vec();
+    // 标志位：小端字节序；有外包框时包含 [minx, maxx, miny, maxy]，否则标记为空几何
+    blob.push(if bbox.is_some() {
+        0b0000_0011
+    } else {
+        0b0001_0001
+    });
+    blob.extend(srs_id.to_le_bytes());
+    if let Some([min_x, min_y, max_x, max_y]) = bbox {
+        for value in [min_x, max_x, min_y, max_y] {
+            blob.extend(value.to_le_bytes());
+        }
+    }
+    blob.extend(geometry.to_wkb());
+    blob
+}
+
+/// 从 GeoPackage 几何二进制中读取外包框 `[minx, maxx, miny, maxy]`
+fn blob_envelope(blob: &[u8]) -> Option<[f64; 4]> {
+    if blob.len() < 8 || &blob[..2] != b"GP" {
+        return None;
+    }
+    let flags = blob[3];
+    let little_endian = flags & 1 == 1;
+    let envelope = (flags >> 1) & 0b111;
+    if flags & 0b0001_0000 != 0 || envelope == 0 || blob.len() < 40 {
+        return None;
+    }
+    let mut values = [0.0; 4];
+    for (i, value) in values.iter_mut().enumerate() {
+        let bytes: [u8; 8] = blob[8 + i * 8..16 + i * 8].try_into().ok()?;
+        *value = if little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        };
+    }
+    Some(values)
+}
+
+/// 注册空间索引触发器用到的 SQL 函数（通常由 GDAL/QGIS 提供）
+fn register_functions(conn: &Connection) -> Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("ST_IsEmpty", 1, flags, |ctx| {
+        Ok(ctx
+            .get_raw(0)
+            .as_blob_or_null()?
+            .map(|blob| blob.len() > 3 && blob[3] & 0b0001_0000 != 0))
+    })?;
+    for (name, index) in [
+        ("ST_MinX", 0),
+        ("ST_MaxX", 1),
+        ("ST_MinY", 2),
+        ("ST_MaxY", 3),
+    ] {
+        conn.create_scalar_function(name, 1, flags, move |ctx| {
+            Ok(ctx
+                .get_raw(0)
+                .as_blob_or_null()?
+                .and_then(blob_envelope)
+                .map(|envelope| envelope[index]))
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+
+    #[test]
+    fn test_geopackage_tables_and_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.gpkg");
+        let mut writer = GeoPackageWriter::create(&path).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.write(&sample_details()).unwrap();
+        let mut street = sample_details_with(
+            "def",
+            r#"{"type":"LineString","coordinates":[[113.0,34.0],[114.0,35.0]]}"#,
+        );
+        street.place_type = "街路巷".to_string();
+        street.place_type_code = "25110".to_string();
+        writer.write(&street).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.count(), 2);
+        writer.finish().unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let application_id: i64 = conn
+            .query_row("PRAGMA application_id", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(application_id, 0x4750_4B47);
+
+        let tables: Vec<(String, i32)> = conn
+            .prepare("SELECT table_name, srs_id FROM gpkg_contents ORDER BY table_name")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            tables,
+            vec![
+                ("place_22200".to_string(), CGCS2000_SRS_ID),
+                ("place_25110".to_string(), CGCS2000_SRS_ID)
+            ]
+        );
+
+        let (name, lon): (String, f64) = conn
+            .query_row(
+                "SELECT standard_name, lon FROM place_22200 WHERE id = 'abc'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "唐庄村");
+        assert!((lon - 113.97).abs() < 1e-9);
+
+        let index: (f64, f64, f64, f64) = conn
+            .query_row(
+                "SELECT minx, maxx, miny, maxy FROM rtree_place_25110_geom",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(index, (113.0, 114.0, 34.0, 35.0));
+
+        let extent: (f64, f64) = conn
+            .query_row(
+                "SELECT min_x, max_y FROM gpkg_contents WHERE table_name = 'place_25110'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(extent, (113.0, 35.0));
+    }
+
+    #[test]
+    fn test_open_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.gpkg");
+        let mut writer = GeoPackageWriter::open(&path).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.flush().unwrap();
+        // 模拟中断：没有调用 finish
+        drop(writer);
+
+        let mut writer = GeoPackageWriter::open(&path).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer
+            .write(&sample_details_with(
+                "def",
+                r#"{"type":"Point","coordinates":[114.5,35.5]}"#,
+            ))
+            .unwrap();
+        assert_eq!(writer.count(), 1);
+        writer.finish().unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let (count, max_x): (i64, f64) = conn
+            .query_row(
+                "SELECT (SELECT count(*) FROM place_22200), max_x FROM gpkg_contents",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((count, max_x), (2, 114.5));
+
+        std::fs::write(dir.path().join("other.gpkg"), b"").unwrap();
+        assert!(GeoPackageWriter::open(dir.path().join("other.gpkg")).is_err());
+    }
+
+    #[test]
+    fn test_geometry_blob_header() {
+        let details = sample_details();
+        let blob = geometry_blob(&details.gdm, CGCS2000_SRS_ID);
+        assert_eq!(&blob[..4], b"GP\0\x03");
+        assert_eq!(&blob[4..8], &CGCS2000_SRS_ID.to_le_bytes());
+        let envelope = blob_envelope(&blob).unwrap();
+        assert_eq!(envelope[0], envelope[1]);
+        assert_eq!(&blob[40..], details.gdm.to_wkb().as_slice());
+
+        assert_eq!(feature_table_name("22200"), "place_22200");
+        assert_eq!(feature_table_name(""), "place_unknown");
+    }
+
+    #[test]
+    fn test_custom_srs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gcj.gpkg");
+        let mut writer = GeoPackageWriter::create(&path).unwrap().crs(Crs::Gcj02);
+        writer.write(&sample_details()).unwrap();
+        writer.finish().unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let (srs, organization): (i32, String) = conn
+            .query_row(
+                "SELECT c.srs_id, s.organization FROM gpkg_contents c
+                 JOIN gpkg_spatial_ref_sys s ON s.srs_id = c.srs_id",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((srs, organization.as_str()), (srs_id(Crs::Gcj02), "NONE"));
+        assert_eq!(srs_id(Crs::gauss_kruger_3(38)), 4526);
+    }
+}
@@ -0,0 +1,122 @@
+//! # GPX 导出
+//!
+//! 把地名写成 GPX 1.1 航点，供手持 GPS 设备导入。每个地名一个航点，
+//! 位置取几何中心点，没有坐标的地名会被跳过。
+
+use super::kml::escape_xml;
+use super::require_geographic;
+use crate::api::DetailsQueryResponse;
+use crate::crs::Crs;
+use crate::error::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 流式 GPX 航点写入器
+pub struct GpxWriter<W: Write> {
+    writer: W,
+    count: usize,
+    skipped: usize,
+    crs: Crs,
+}
+
+impl GpxWriter<BufWriter<File>> {
+    /// 创建（或清空）GPX 文件
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> GpxWriter<W> {
+    /// 写出 GPX 文件头
+    pub fn new(mut writer: W) -> Result<Self> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<gpx version="1.1" creator="henan-toponym" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )?;
+        Ok(Self {
+            writer,
+            count: 0,
+            skipped: 0,
+            crs: Crs::default(),
+        })
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；GPX 只支持经纬度坐标系
+    pub fn crs(mut self, crs: Crs) -> Result<Self> {
+        require_geographic(crs, "GPX")?;
+        self.crs = crs;
+        Ok(self)
+    }
+
+    /// 写入一个航点，没有坐标时跳过
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        let Some(center) = details.gdm.centroid() else {
+            self.skipped += 1;
+            return Ok(());
+        };
+        let (lon, lat) = Crs::default().transform(self.crs, center);
+        let mut desc = details.place_meaning.clone();
+        if !details.place_origin.is_empty() {
+            if !desc.is_empty() {
+                desc.push('\n');
+            }
+            desc.push_str(&details.place_origin);
+        }
+        writeln!(
+            self.writer,
+            r#"<wpt lat="{}" lon="{}"><name>{}</name><cmt>{}</cmt><desc>{}</desc><type>{}</type></wpt>"#,
+            lat,
+            lon,
+            escape_xml(&details.standard_name),
+            escape_xml(&details.roman_alphabet_spelling),
+            escape_xml(&desc),
+            escape_xml(&details.place_type),
+        )?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已写入的航点数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 因没有坐标而跳过的地名数
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// 写出文件结尾并取回底层输出
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.writer, "</gpx>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+
+    #[test]
+    fn test_waypoints() {
+        let mut writer = GpxWriter::new(Vec::new()).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer
+            .write(&sample_details_with(
+                "empty",
+                r#"{"type":"MultiPoint","coordinates":[]}"#,
+            ))
+            .unwrap();
+        assert_eq!((writer.count(), writer.skipped()), (1, 1));
+        let gpx = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert!(gpx.contains(r#"<wpt lat="34.72" lon="113.97"><name>唐庄村</name>"#));
+        assert!(gpx.contains("<desc>因唐姓聚居得名\n明代建村, 名为&quot;唐庄&quot;</desc>"));
+        assert!(gpx.contains("<type>农村居民点</type>"));
+        assert!(gpx.trim_end().ends_with("</gpx>"));
+    }
+}
@@ -0,0 +1,277 @@
+//! # KML 导出
+//!
+//! 供 Google Earth 及奥维等外业软件使用。地标按市、区县分文件夹组织，
+//! 气泡中显示拼音、类别、地名含义与来历。分组需要全部数据，
+//! 因此 [`KmlWriter`] 先在内存中按行政区划归类，调用 [`KmlWriter::finish`] 时一次写出。
+
+use super::{reproject, require_geographic};
+use crate::api::{DetailsQueryResponse, Geometry, Position};
+use crate::crs::Crs;
+use crate::error::Result;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 缺少行政区划信息时使用的文件夹名
+const UNKNOWN_FOLDER: &str = "未知";
+
+/// 文件夹键：`(行政代码, 名称)`，按代码排序
+type FolderKey = (String, String);
+
+/// KML 导出器
+pub struct KmlWriter<W: Write> {
+    writer: W,
+    name: String,
+    /// 市 -> 区县 -> 地标
+    folders: BTreeMap<FolderKey, BTreeMap<FolderKey, Vec<String>>>,
+    count: usize,
+    crs: Crs,
+}
+
+impl KmlWriter<BufWriter<File>> {
+    /// 创建（或清空）KML 文件
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> KmlWriter<W> {
+    /// 包装任意输出
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            name: "地名".to_string(),
+            folders: BTreeMap::new(),
+            count: 0,
+            crs: Crs::default(),
+        }
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；KML 只支持经纬度坐标系
+    pub fn crs(mut self, crs: Crs) -> Result<Self> {
+        require_geographic(crs, "KML")?;
+        self.crs = crs;
+        Ok(self)
+    }
+
+    /// 设置文档名称，默认为 "地名"
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// 添加一条地名详情
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        let city = folder_key(details.city.as_deref(), details.city_name.as_deref());
+        let area = folder_key(details.area.as_deref(), details.area_name.as_deref());
+        self.folders
+            .entry(city)
+            .or_default()
+            .entry(area)
+            .or_default()
+            .push(placemark(&reproject(details, self.crs)));
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已添加的地标数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 写出完整的 KML 文档并取回底层输出
+    pub fn finish(mut self) -> Result<W> {
+        let w = &mut self.writer;
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(w, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+        writeln!(w, "<Document>")?;
+        writeln!(w, "<name>{}</name>", escape_xml(&self.name))?;
+        for ((_, city), areas) in &self.folders {
+            writeln!(w, "<Folder>")?;
+            writeln!(w, "<name>{}</name>", escape_xml(city))?;
+            for ((_, area), placemarks) in areas {
+                writeln!(w, "<Folder>")?;
+                writeln!(w, "<name>{}</name>", escape_xml(area))?;
+                for placemark in placemarks {
+                    writeln!(w, "{}", placemark)?;
+                }
+                writeln!(w, "</Folder>")?;
+            }
+            writeln!(w, "</Folder>")?;
+        }
+        writeln!(w, "</Document>")?;
+        writeln!(w, "</kml>")?;
+        w.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn folder_key(code: Option<&str>, name: Option<&str>) -> FolderKey {
+    let name = name.filter(|n| !n.is_empty()).unwrap_or(UNKNOWN_FOLDER);
+    (code.unwrap_or_default().to_string(), name.to_string())
+}
+
+/// 生成地标，气泡内容为 HTML 表格
+fn placemark(details: &DetailsQueryResponse) -> String {
+    let rows = [
+        ("拼音", details.roman_alphabet_spelling.as_str()),
+        ("类别", details.place_type.as_str()),
+        ("地名代码", details.place_code.as_str()),
+        ("地名含义", details.place_meaning.as_str()),
+        ("地名来历", details.place_origin.as_str()),
+        (
+            "历史沿革",
+            details.government_history.as_deref().unwrap_or_default(),
+        ),
+    ];
+    let rows: String = rows
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(label, value)| format!("<tr><th>{}</th><td>{}</td></tr>", label, escape_xml(value)))
+        .collect();
+    format!(
+        "<Placemark id=\"{}\"><name>{}</name><description><![CDATA[<table>{}</table>]]></description>{}</Placemark>",
+        escape_xml(&details.id),
+        escape_xml(&details.standard_name),
+        rows.replace("]]>", "]]]]><![CDATA[>"),
+        kml_geometry(&details.gdm)
+    )
+}
+
+/// 转换为 KML 几何元素
+fn kml_geometry(geometry: &Geometry) -> String {
+    fn coordinates(positions: &[Position]) -> String {
+        let points: Vec<String> = positions
+            .iter()
+            .filter_map(|p| match p.as_slice() {
+                [x, y, ..] => Some(format!("{},{}", x, y)),
+                _ => None,
+            })
+            .collect();
+        format!("<coordinates>{}</coordinates>", points.join(" "))
+    }
+    fn polygon(rings: &[Vec<Position>]) -> String {
+        let mut out = String::from("<Polygon>");
+        for (i, ring) in rings.iter().enumerate() {
+            let tag = if i == 0 {
+                "outerBoundaryIs"
+            } else {
+                "innerBoundaryIs"
+            };
+            out.push_str(&format!(
+                "<{tag}><LinearRing>{}</LinearRing></{tag}>",
+                coordinates(ring)
+            ));
+        }
+        out.push_str("</Polygon>");
+        out
+    }
+    fn multi(parts: impl Iterator<Item = String>) -> String {
+        format!(
+            "<MultiGeometry>{}</MultiGeometry>",
+            parts.collect::<String>()
+        )
+    }
+
+    match geometry {
+        Geometry::Point { coordinates: p } => {
+            format!("<Point>{}</Point>", coordinates(std::slice::from_ref(p)))
+        }
+        Geometry::MultiPoint {
+            coordinates: points,
+        } => multi(
+            points
+                .iter()
+                .map(|p| format!("<Point>{}</Point>", coordinates(std::slice::from_ref(p)))),
+        ),
+        Geometry::LineString { coordinates: line } => {
+            format!("<LineString>{}</LineString>", coordinates(line))
+        }
+        Geometry::MultiLineString { coordinates: lines } => multi(
+            lines
+                .iter()
+                .map(|line| format!("<LineString>{}</LineString>", coordinates(line))),
+        ),
+        Geometry::Polygon { coordinates: rings } => polygon(rings),
+        Geometry::MultiPolygon {
+            coordinates: polygons,
+        } => multi(polygons.iter().map(|rings| polygon(rings))),
+        Geometry::GeometryCollection { geometries } => multi(geometries.iter().map(kml_geometry)),
+    }
+}
+
+/// 转义 XML 特殊字符
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+
+    #[test]
+    fn test_folders_and_balloon() {
+        let mut writer = KmlWriter::new(Vec::new()).name("中牟县农村居民点");
+        writer.write(&sample_details()).unwrap();
+        let mut other = sample_details_with(
+            "def",
+            r#"{"type":"LineString","coordinates":[[113.0,34.0],[114.0,35.0]]}"#,
+        );
+        other.area = Some("410102000000".to_string());
+        other.area_name = Some("中原区".to_string());
+        other.standard_name = "A&B路".to_string();
+        writer.write(&other).unwrap();
+        assert_eq!(writer.count(), 2);
+        let kml = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert!(kml.contains("<name>中牟县农村居民点</name>"));
+        assert_eq!(kml.matches("<Folder>").count(), 3);
+        // 区县文件夹按行政代码排序
+        assert!(
+            kml.find("<name>中原区</name>").unwrap() < kml.find("<name>中牟县</name>").unwrap()
+        );
+        assert!(kml.contains("<tr><th>地名含义</th><td>因唐姓聚居得名</td></tr>"));
+        assert!(kml.contains("明代建村, 名为&quot;唐庄&quot;"));
+        assert!(kml.contains("<name>A&amp;B路</name>"));
+        assert!(kml.contains("<Point><coordinates>113.97,34.72</coordinates></Point>"));
+        assert!(kml.contains("<LineString><coordinates>113,34 114,35</coordinates></LineString>"));
+    }
+
+    #[test]
+    fn test_polygon_geometry() {
+        let geometry: Geometry = serde_json::from_str(
+            r#"{"type":"Polygon","coordinates":[[[0,0],[2,0],[2,2],[0,0]],[[0.5,0.5],[1,0.5],[1,1],[0.5,0.5]]]}"#,
+        )
+        .unwrap();
+        let kml = kml_geometry(&geometry);
+        assert!(kml.starts_with("<Polygon><outerBoundaryIs><LinearRing><coordinates>0,0 2,0"));
+        assert!(kml.contains("<innerBoundaryIs>"));
+    }
+
+    #[test]
+    fn test_crs_option() {
+        assert!(
+            KmlWriter::new(Vec::new())
+                .crs(Crs::gauss_kruger_3(38))
+                .is_err()
+        );
+        let mut writer = KmlWriter::new(Vec::new()).crs(Crs::Gcj02).unwrap();
+        writer.write(&sample_details()).unwrap();
+        let kml = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let (lon, lat) = Crs::Cgcs2000.transform(Crs::Gcj02, (113.97, 34.72));
+        assert!(kml.contains(&format!("<coordinates>{},{}</coordinates>", lon, lat)));
+    }
+}
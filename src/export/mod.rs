@@ -0,0 +1,274 @@
+//! # 数据导出
+//!
+//! 把采集到的地名详情 [`DetailsQueryResponse`](crate::api::DetailsQueryResponse)
+//! 导出为各种文件格式。各导出器默认保留接口的 CGCS2000 经纬度坐标，
+//! 也可以通过 `crs` 选项转换到其他坐标系，参见 [`Crs`]。
+
+use crate::api::DetailsQueryResponse;
+use crate::crs::Crs;
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+pub mod csv;
+pub use self::csv::*;
+
+pub mod geojson;
+pub use geojson::*;
+
+pub mod geopackage;
+pub use geopackage::*;
+
+pub mod shapefile;
+pub use shapefile::*;
+
+pub mod kml;
+pub use kml::*;
+
+pub mod gpx;
+pub use gpx::*;
+
+pub mod parquet;
+pub use self::parquet::*;
+
+/// 把地名详情的坐标从接口坐标系转换到目标坐标系，无需转换时不复制
+pub(crate) fn reproject(details: &DetailsQueryResponse, crs: Crs) -> Cow<'_, DetailsQueryResponse> {
+    if crs == Crs::default() {
+        return Cow::Borrowed(details);
+    }
+    let mut details = details.clone();
+    Crs::default().transform_geometry(crs, &mut details.gdm);
+    Cow::Owned(details)
+}
+
+/// 只支持经纬度坐标的格式（KML、GPX）检查坐标系
+pub(crate) fn require_geographic(crs: Crs, format: &str) -> crate::Result<()> {
+    if crs.is_geographic() {
+        Ok(())
+    } else {
+        Err(crate::Error::Config(format!(
+            "{} 只支持经纬度坐标系，不支持 {}",
+            format, crs
+        )))
+    }
+}
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// CSV，几何对象为 WKT
+    Csv,
+    /// GeoJSON FeatureCollection，只支持经纬度坐标系
+    GeoJson,
+    /// GeoPackage，每个地名类别一张要素表
+    GeoPackage,
+    /// ESRI Shapefile，按几何类型分为多个文件
+    Shapefile,
+    /// KML，只支持经纬度坐标系
+    Kml,
+    /// GPX 航点，只支持经纬度坐标系
+    Gpx,
+    /// Parquet，输出为按市级代码分区的目录
+    Parquet,
+}
+
+impl ExportFormat {
+    /// 全部导出格式
+    pub const ALL: [Self; 7] = [
+        Self::Csv,
+        Self::GeoJson,
+        Self::GeoPackage,
+        Self::Shapefile,
+        Self::Kml,
+        Self::Gpx,
+        Self::Parquet,
+    ];
+
+    /// 格式名称，同时也是文件扩展名
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::GeoJson => "geojson",
+            Self::GeoPackage => "gpkg",
+            Self::Shapefile => "shp",
+            Self::Kml => "kml",
+            Self::Gpx => "gpx",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    /// 按文件扩展名推断格式
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::GeoJson),
+            extension => extension.parse().ok(),
+        }
+    }
+
+    /// 把地名详情导出到 `path`，坐标转换到 `crs`，返回导出的条数
+    pub fn export<'a>(
+        self,
+        path: impl AsRef<Path>,
+        crs: Crs,
+        details: impl IntoIterator<Item = &'a DetailsQueryResponse>,
+    ) -> crate::Result<usize> {
+        let path = path.as_ref();
+        let mut count = 0;
+        match self {
+            Self::Csv => {
+                let mut writer = CsvExporter::create(path)?.crs(crs);
+                for details in details {
+                    writer.write(details)?;
+                    count += 1;
+                }
+                writer.flush()?;
+            }
+            Self::GeoJson => {
+                let mut writer = GeoJsonWriter::create(path)?.crs(crs)?;
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+            Self::GeoPackage => {
+                let mut writer = GeoPackageWriter::create(path)?.crs(crs);
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+            Self::Shapefile => {
+                let options = ShapefileOptions::default().crs(crs);
+                let mut writer = ShapefileWriter::with_options(path, options)?;
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+            Self::Kml => {
+                let mut writer = KmlWriter::create(path)?.crs(crs)?;
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+            Self::Gpx => {
+                let mut writer = GpxWriter::create(path)?.crs(crs)?;
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+            Self::Parquet => {
+                let options = ParquetOptions::default().crs(crs);
+                let mut writer = ParquetWriter::with_options(path, options)?;
+                for details in details {
+                    writer.write(details)?;
+                }
+                count = writer.count();
+                writer.finish()?;
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 按格式名称反序列化，如任务文件中的 `formats = ["csv", "shp"]`
+impl<'de> serde::Deserialize<'de> for ExportFormat {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = crate::Error;
+
+    /// 解析格式名称：`csv`、`geojson`、`gpkg`、`shp`、`kml`、`gpx` 或 `parquet`
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| {
+                crate::Error::Config(format!(
+                    "无法识别的导出格式: {}（支持 csv、geojson、gpkg、shp、kml、gpx、parquet）",
+                    s
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use crate::api::DetailsQueryResponse;
+    use std::io::Write;
+    use std::path::Path;
+
+    /// 模拟写到一半时中断：在文件末尾追加没有写完的内容
+    pub fn write_partial(path: &Path, partial: &[u8]) {
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(partial)
+            .unwrap();
+    }
+
+    /// 测试用的地名详情，`gdm` 为点
+    pub fn sample_details() -> DetailsQueryResponse {
+        sample_details_with("abc", r#"{"type":"Point","coordinates":[113.97,34.72]}"#)
+    }
+
+    /// 测试用的地名详情，指定 ID 和 `gdm`
+    pub fn sample_details_with(id: &str, gdm: &str) -> DetailsQueryResponse {
+        serde_json::from_str(&format!(
+            r#"{{"area_name":"中牟县","city_name":"郑州市","old_name":null,"ethnic_minorities_writing":"","gdm":{gdm},"government_history":"1949年属中牟县","id":"{id}","place_code":"410122000000","place_meaning":"因唐姓聚居得名","place_origin":"明代建村, 名为\"唐庄\"","place_type":"农村居民点","place_type_code":"22200","province_name":"河南省","roman_alphabet_spelling":"Tángzhuāng Cūn","standard_name":"唐庄村","area":"410122000000","city":"410100000000","province":"410000000000"}}"#
+        ))
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::sample_details;
+    use super::*;
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!(
+            ExportFormat::from_path("out/settlements.GeoJSON"),
+            Some(ExportFormat::GeoJson)
+        );
+        assert_eq!(
+            ExportFormat::from_path("settlements.json"),
+            Some(ExportFormat::GeoJson)
+        );
+        assert_eq!(ExportFormat::from_path("settlements"), None);
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        for format in ExportFormat::ALL {
+            let path = dir.path().join(format!("settlements.{}", format));
+            let count = format
+                .export(&path, Crs::default(), [&sample_details()])
+                .unwrap();
+            assert_eq!(count, 1, "{}", format);
+        }
+        assert!(dir.path().join("settlements.csv").exists());
+        assert!(dir.path().join("settlements.parquet").is_dir());
+    }
+}
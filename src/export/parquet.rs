@@ -0,0 +1,472 @@
+//! # Parquet 导出
+//!
+//! 供 Polars、DuckDB 等分析工具使用的列式文件。各列都有明确类型：
+//! 行政代码保存为字符串，`lon`、`lat` 为 `f64`，地名类别和几何类型为字典编码
+//! （Polars 中读取为 `Categorical`），几何对象以 WKB 保存在 `wkb` 列。
+//!
+//! 默认按市级行政代码分区，输出 Hive 风格的目录：
+//!
+//! ```text
+//! settlements/
+//! ├── city_code=410100000000/part-0.parquet
+//! ├── city_code=410200000000/part-0.parquet
+//! └── city_code=__HIVE_DEFAULT_PARTITION__/part-0.parquet   # 缺少市级代码或代码不是数字
+//! ```
+//!
+//! 分区列名为 `city_code`，与文件中的 `city` 列不重名，读取时可直接开启 Hive 分区。
+
+use super::reproject;
+use crate::api::DetailsQueryResponse;
+use crate::crs::Crs;
+use crate::error::{Error, Result};
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::Compression;
+use ::parquet::errors::ParquetError;
+use ::parquet::file::properties::WriterProperties;
+use arrow_array::types::Int32Type;
+use arrow_array::{ArrayRef, BinaryArray, DictionaryArray, Float64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 分区列名
+pub const PARTITION_COLUMN: &str = "city_code";
+
+/// 缺少市级行政代码时的分区值，Hive 约定读取为空值
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// 每个分区文件名
+const PART_FILE: &str = "part-0.parquet";
+
+/// 字典编码的字符串列
+fn categorical() -> DataType {
+    DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+}
+
+/// Parquet 文件的列定义，模式元数据 `crs` 记录坐标系
+pub fn parquet_schema(crs: Crs) -> SchemaRef {
+    let text = |name: &str, nullable: bool| Field::new(name, DataType::Utf8, nullable);
+    let fields = vec![
+        text("id", false),
+        text("standard_name", false),
+        text("roman_alphabet_spelling", false),
+        Field::new("place_type", categorical(), false),
+        Field::new("place_type_code", categorical(), false),
+        text("place_code", false),
+        text("province", false),
+        text("province_name", false),
+        text("city", true),
+        text("city_name", true),
+        text("area", true),
+        text("area_name", true),
+        text("old_name", true),
+        text("ethnic_minorities_writing", false),
+        text("place_meaning", false),
+        text("place_origin", false),
+        text("government_history", true),
+        Field::new("lon", DataType::Float64, true),
+        Field::new("lat", DataType::Float64, true),
+        Field::new("geometry_type", categorical(), false),
+        Field::new("wkb", DataType::Binary, false),
+    ];
+    let metadata = HashMap::from([("crs".to_string(), crs.to_string())]);
+    Arc::new(Schema::new_with_metadata(fields, metadata))
+}
+
+/// Parquet 导出选项
+#[derive(Debug, Clone, Copy)]
+pub struct ParquetOptions {
+    partition_by_city: bool,
+    batch_size: usize,
+    crs: Crs,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self {
+            partition_by_city: true,
+            batch_size: 8192,
+            crs: Crs::default(),
+        }
+    }
+}
+
+impl ParquetOptions {
+    /// 是否按市级行政代码分区，默认分区；不分区时输出单个文件
+    pub fn partition_by_city(mut self, partition: bool) -> Self {
+        self.partition_by_city = partition;
+        self
+    }
+
+    /// 每个行组的行数，默认 8192
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 输出坐标系，默认 CGCS2000；投影坐标系下 `lon`、`lat` 列为东、北坐标
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+}
+
+/// 删除分区目录中已有的分区；目录中有分区以外的内容时不删除任何文件并报错
+fn clear_partitions(dir: &Path) -> Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.collect::<std::io::Result<Vec<_>>>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let prefix = format!("{}=", PARTITION_COLUMN);
+    if let Some(other) = entries.iter().find(|entry| {
+        !entry.file_name().to_string_lossy().starts_with(&prefix)
+            || !entry.file_type().is_ok_and(|t| t.is_dir())
+    }) {
+        return Err(Error::Config(format!(
+            "Parquet 分区目录 {} 中有其他文件 {}，请指定空目录",
+            dir.display(),
+            other.file_name().to_string_lossy()
+        )));
+    }
+    for entry in entries {
+        std::fs::remove_dir_all(entry.path())?;
+    }
+    Ok(())
+}
+
+/// 一个输出文件及尚未写出的行
+struct Partition {
+    path: PathBuf,
+    writer: ArrowWriter<File>,
+    rows: Vec<DetailsQueryResponse>,
+}
+
+/// Parquet 导出器
+///
+/// 地名先在内存中按分区缓存，满一个行组后写出；调用 [`ParquetWriter::finish`]
+/// 写出剩余数据和文件尾，否则文件不完整。
+pub struct ParquetWriter {
+    path: PathBuf,
+    options: ParquetOptions,
+    schema: SchemaRef,
+    partitions: BTreeMap<String, Partition>,
+    count: usize,
+}
+
+impl ParquetWriter {
+    /// 按默认选项创建导出器，`path` 为分区目录
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_options(path, ParquetOptions::default())
+    }
+
+    /// 按指定选项创建导出器：分区时 `path` 为目录，否则为文件路径，已有的同名文件会被覆盖
+    ///
+    /// 分区目录中之前导出的 `city_code=…` 分区会先全部删除，以免读取时混入旧数据；
+    /// 目录中有其他文件时返回 [`Error::Config`]
+    pub fn with_options(path: impl AsRef<Path>, options: ParquetOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if options.partition_by_city {
+            clear_partitions(&path)?;
+            std::fs::create_dir_all(&path)?;
+        }
+        Ok(Self {
+            path,
+            schema: parquet_schema(options.crs),
+            options,
+            partitions: BTreeMap::new(),
+            count: 0,
+        })
+    }
+
+    /// 写入一条地名详情
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        let key = if self.options.partition_by_city {
+            details
+                .city
+                .as_deref()
+                // 分区键用作目录名，只接受数字代码，防止写到输出目录之外
+                .filter(|code| !code.is_empty() && code.bytes().all(|b| b.is_ascii_digit()))
+                .unwrap_or(DEFAULT_PARTITION)
+                .to_string()
+        } else {
+            String::new()
+        };
+        if !self.partitions.contains_key(&key) {
+            let partition = self.open_partition(&key)?;
+            self.partitions.insert(key.clone(), partition);
+        }
+        let partition = self.partitions.get_mut(&key).expect("分区已创建");
+        partition
+            .rows
+            .push(reproject(details, self.options.crs).into_owned());
+        if partition.rows.len() >= self.options.batch_size {
+            write_rows(&self.schema, partition)?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已写入的地名数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 已创建的文件
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.partitions.values().map(|p| p.path.clone()).collect()
+    }
+
+    /// 把缓存的行写为新的行组
+    pub fn flush(&mut self) -> Result<()> {
+        for partition in self.partitions.values_mut() {
+            write_rows(&self.schema, partition)?;
+            partition.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 写出剩余数据和文件尾，返回全部输出文件
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::with_capacity(self.partitions.len());
+        for (_, mut partition) in std::mem::take(&mut self.partitions) {
+            write_rows(&self.schema, &mut partition)?;
+            partition.writer.close()?;
+            files.push(partition.path);
+        }
+        Ok(files)
+    }
+
+    fn open_partition(&self, key: &str) -> Result<Partition> {
+        let path = if self.options.partition_by_city {
+            let dir = self.path.join(format!("{}={}", PARTITION_COLUMN, key));
+            std::fs::create_dir_all(&dir)?;
+            dir.join(PART_FILE)
+        } else {
+            self.path.clone()
+        };
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_row_count(Some(self.options.batch_size))
+            .build();
+        let writer =
+            ArrowWriter::try_new(File::create(&path)?, self.schema.clone(), Some(properties))?;
+        Ok(Partition {
+            path,
+            writer,
+            rows: Vec::new(),
+        })
+    }
+}
+
+/// 把分区中缓存的行写出
+fn write_rows(schema: &SchemaRef, partition: &mut Partition) -> Result<()> {
+    if partition.rows.is_empty() {
+        return Ok(());
+    }
+    let batch = record_batch(schema, &partition.rows)?;
+    partition.writer.write(&batch)?;
+    partition.rows.clear();
+    Ok(())
+}
+
+/// 按 [`parquet_schema`] 的列顺序生成记录批次
+pub fn record_batch(schema: &SchemaRef, rows: &[DetailsQueryResponse]) -> Result<RecordBatch> {
+    fn text<'a>(
+        rows: &'a [DetailsQueryResponse],
+        f: impl Fn(&'a DetailsQueryResponse) -> &'a str,
+    ) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(rows.iter().map(f)))
+    }
+    fn optional<'a>(
+        rows: &'a [DetailsQueryResponse],
+        f: impl Fn(&'a DetailsQueryResponse) -> Option<&'a str>,
+    ) -> ArrayRef {
+        Arc::new(rows.iter().map(f).collect::<StringArray>())
+    }
+    fn categorical<'a>(
+        rows: &'a [DetailsQueryResponse],
+        f: impl Fn(&'a DetailsQueryResponse) -> &'a str,
+    ) -> ArrayRef {
+        Arc::new(rows.iter().map(f).collect::<DictionaryArray<Int32Type>>())
+    }
+
+    let centers: Vec<_> = rows.iter().map(|d| d.gdm.centroid()).collect();
+    let columns: Vec<ArrayRef> = vec![
+        text(rows, |d| &d.id),
+        text(rows, |d| &d.standard_name),
+        text(rows, |d| &d.roman_alphabet_spelling),
+        categorical(rows, |d| &d.place_type),
+        categorical(rows, |d| &d.place_type_code),
+        text(rows, |d| &d.place_code),
+        text(rows, |d| &d.province),
+        text(rows, |d| &d.province_name),
+        optional(rows, |d| d.city.as_deref()),
+        optional(rows, |d| d.city_name.as_deref()),
+        optional(rows, |d| d.area.as_deref()),
+        optional(rows, |d| d.area_name.as_deref()),
+        optional(rows, |d| d.old_name.as_deref()),
+        text(rows, |d| &d.ethnic_minorities_writing),
+        text(rows, |d| &d.place_meaning),
+        text(rows, |d| &d.place_origin),
+        optional(rows, |d| d.government_history.as_deref()),
+        Arc::new(
+            centers
+                .iter()
+                .map(|c| c.map(|(lon, _)| lon))
+                .collect::<Float64Array>(),
+        ),
+        Arc::new(
+            centers
+                .iter()
+                .map(|c| c.map(|(_, lat)| lat))
+                .collect::<Float64Array>(),
+        ),
+        categorical(rows, |d| d.gdm.type_name()),
+        Arc::new(BinaryArray::from_iter_values(
+            rows.iter().map(|d| d.gdm.to_wkb()),
+        )),
+    ];
+    Ok(RecordBatch::try_new(schema.clone(), columns).map_err(ParquetError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+    use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use arrow_array::Array;
+
+    /// 读取文件的模式和全部记录批次
+    fn read(path: &Path) -> (SchemaRef, Vec<RecordBatch>) {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+        let schema = builder.schema().clone();
+        let batches = builder
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        (schema, batches)
+    }
+
+    #[test]
+    fn test_invalid_city_code_uses_default_partition() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("settlements");
+        let mut writer = ParquetWriter::create(&out).unwrap();
+        let mut details = sample_details();
+        details.city = Some("../../escape".to_string());
+        writer.write(&details).unwrap();
+        let files = writer.finish().unwrap();
+        assert_eq!(
+            files,
+            vec![
+                out.join("city_code=__HIVE_DEFAULT_PARTITION__")
+                    .join(PART_FILE)
+            ]
+        );
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[test]
+    fn test_partitioned_by_city() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ParquetOptions::default().batch_size(1);
+        let mut writer = ParquetWriter::with_options(dir.path(), options).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer
+            .write(&sample_details_with(
+                "def",
+                r#"{"type":"LineString","coordinates":[[113.0,34.0],[114.0,35.0]]}"#,
+            ))
+            .unwrap();
+        let mut other = sample_details_with("ghi", r#"{"type":"MultiPoint","coordinates":[]}"#);
+        other.city = None;
+        writer.write(&other).unwrap();
+        assert_eq!(writer.count(), 3);
+        let files = writer.finish().unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                dir.path().join("city_code=410100000000").join(PART_FILE),
+                dir.path()
+                    .join("city_code=__HIVE_DEFAULT_PARTITION__")
+                    .join(PART_FILE),
+            ]
+        );
+        let (schema, batches) = read(&files[0]);
+        // 每行一个行组
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        let batch = &batches[0];
+        assert_eq!(schema.metadata()["crs"], "CGCS2000");
+        assert_eq!(
+            batch
+                .schema()
+                .field_with_name("place_type")
+                .unwrap()
+                .data_type(),
+            &categorical()
+        );
+        let lon = batch.column_by_name("lon").unwrap();
+        let lon = lon.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(lon.value(0), 113.97);
+        let wkb = batch.column_by_name("wkb").unwrap();
+        let wkb = wkb.as_any().downcast_ref::<BinaryArray>().unwrap();
+        assert_eq!(wkb.value(0), sample_details().gdm.to_wkb().as_slice());
+
+        let batch = &read(&files[1]).1[0];
+        assert!(batch.column_by_name("city").unwrap().is_null(0));
+        assert!(batch.column_by_name("lon").unwrap().is_null(0));
+    }
+
+    #[test]
+    fn test_reexport_replaces_partitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("settlements");
+        let mut writer = ParquetWriter::create(&out).unwrap();
+        let mut kaifeng = sample_details_with("def", r#"{"type":"Point","coordinates":[1,2]}"#);
+        kaifeng.city = Some("410200000000".to_string());
+        writer.write(&sample_details()).unwrap();
+        writer.write(&kaifeng).unwrap();
+        writer.finish().unwrap();
+
+        // 再次导出到同一目录，上次的开封市分区不应留下
+        let mut writer = ParquetWriter::create(&out).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.finish().unwrap();
+        let partitions: Vec<_> = std::fs::read_dir(&out)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(partitions, ["city_code=410100000000"]);
+
+        // 目录中有其他文件时拒绝导出
+        std::fs::write(out.join("notes.txt"), "").unwrap();
+        assert!(matches!(ParquetWriter::create(&out), Err(Error::Config(_))));
+        assert!(out.join("city_code=410100000000").exists());
+    }
+
+    #[test]
+    fn test_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settlements.parquet");
+        let crs = Crs::gauss_kruger_3(38);
+        let options = ParquetOptions::default().partition_by_city(false).crs(crs);
+        let mut writer = ParquetWriter::with_options(&path, options).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.files(), vec![path.clone()]);
+        writer.finish().unwrap();
+
+        let (schema, batches) = read(&path);
+        assert_eq!(schema.metadata()["crs"], crs.to_string());
+        let batch = &batches[0];
+        let lon = batch.column_by_name("lon").unwrap();
+        let lon = lon.as_any().downcast_ref::<Float64Array>().unwrap();
+        let (x, _) = Crs::Cgcs2000.transform(crs, (113.97, 34.72));
+        assert_eq!(lon.value(0), x);
+    }
+}
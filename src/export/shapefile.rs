@@ -0,0 +1,709 @@
+//! # ESRI Shapefile 导出
+//!
+//! Shapefile 每个文件只能容纳一种几何类型，因此按几何类型分层写出
+//! `<名称>_point`、`<名称>_multipoint`、`<名称>_line` 和 `<名称>_polygon`，
+//! 每层包含 `.shp/.shx/.dbf/.prj/.cpg` 五个文件，只创建实际用到的层。
+//!
+//! DBF 字段名最长 10 个字符、文本字段最长 254 字节，属性列因此使用缩写，
+//! 超长文本按 [`LongTextPolicy`] 截断或另存到旁路表；文本编码可选 GBK 或 UTF-8。
+
+use super::csv::FlatRecord;
+use super::reproject;
+use crate::api::{DetailsQueryResponse, Geometry, Position};
+use crate::crs::Crs;
+use crate::error::{Error, Result};
+use crate::time;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// DBF 文本字段最大字节数
+pub const DBF_TEXT_MAX_LEN: usize = 254;
+
+/// DBF 文本字段：`(DBF 字段名, 原字段名, 字段长度)`
+const TEXT_FIELDS: [(&str, &str, usize); 17] = [
+    ("id", "id", 64),
+    ("name", "standard_name", DBF_TEXT_MAX_LEN),
+    ("pinyin", "roman_alphabet_spelling", DBF_TEXT_MAX_LEN),
+    ("type", "place_type", 50),
+    ("type_code", "place_type_code", 10),
+    ("place_code", "place_code", 20),
+    ("province", "province", 20),
+    ("prov_name", "province_name", 50),
+    ("city", "city", 20),
+    ("city_name", "city_name", 50),
+    ("area", "area", 20),
+    ("area_name", "area_name", 50),
+    ("old_name", "old_name", DBF_TEXT_MAX_LEN),
+    ("minority", "ethnic_minorities_writing", DBF_TEXT_MAX_LEN),
+    ("meaning", "place_meaning", DBF_TEXT_MAX_LEN),
+    ("origin", "place_origin", DBF_TEXT_MAX_LEN),
+    ("history", "government_history", DBF_TEXT_MAX_LEN),
+];
+
+/// DBF 数值字段：`(DBF 字段名, 字段长度, 小数位数)`
+///
+/// 字段长度按投影坐标留足整数位，如带带号的高斯-克吕格横坐标 `38500000.12345678901`
+const NUMERIC_FIELDS: [(&str, usize, usize); 2] = [("lon", 24, 11), ("lat", 24, 11)];
+
+/// DBF 文本编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DbfEncoding {
+    /// GBK（代码页 936），兼容旧版 ArcGIS 等软件
+    #[default]
+    Gbk,
+    /// UTF-8
+    Utf8,
+}
+
+impl DbfEncoding {
+    /// `.cpg` 文件内容
+    fn code_page(self) -> &'static str {
+        match self {
+            Self::Gbk => "GBK",
+            Self::Utf8 => "UTF-8",
+        }
+    }
+
+    /// DBF 头中的语言驱动标识
+    fn language_driver(self) -> u8 {
+        match self {
+            Self::Gbk => 0x4D,
+            Self::Utf8 => 0x00,
+        }
+    }
+
+    /// 编码单个字符，GBK 无法表示的字符写为 `&#NNNN;`
+    fn encode_char(self, c: char, out: &mut Vec<u8>) {
+        let mut buf = [0u8; 4];
+        let s = c.encode_utf8(&mut buf);
+        match self {
+            Self::Utf8 => out.extend_from_slice(s.as_bytes()),
+            Self::Gbk => out.extend_from_slice(&encoding_rs::GBK.encode(s).0),
+        }
+    }
+
+    /// 编码文本，最多 `max_len` 字节且不截断多字节字符；返回编码结果及是否被截断
+    fn encode_truncated(self, text: &str, max_len: usize) -> (Vec<u8>, bool) {
+        let mut out = Vec::with_capacity(text.len().min(max_len));
+        let mut ch = Vec::with_capacity(8);
+        for c in text.chars() {
+            ch.clear();
+            self.encode_char(c, &mut ch);
+            if out.len() + ch.len() > max_len {
+                return (out, true);
+            }
+            out.extend_from_slice(&ch);
+        }
+        (out, false)
+    }
+}
+
+/// 超过 DBF 字段长度的文本的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LongTextPolicy {
+    /// 直接截断
+    Truncate,
+    /// 截断后把完整文本另存到 `<名称>_long_text.csv`（列为 `id, field, value`）
+    #[default]
+    Sidecar,
+}
+
+/// Shapefile 导出选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShapefileOptions {
+    encoding: DbfEncoding,
+    long_text: LongTextPolicy,
+    crs: Crs,
+}
+
+impl ShapefileOptions {
+    /// 设置 DBF 文本编码，默认 GBK
+    pub fn encoding(mut self, encoding: DbfEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// 设置超长文本的处理方式，默认另存到旁路表
+    pub fn long_text(mut self, policy: LongTextPolicy) -> Self {
+        self.long_text = policy;
+        self
+    }
+
+    /// 设置输出坐标系，默认 CGCS2000；`.prj` 文件随之写出
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+}
+
+/// Shapefile 几何类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShapeKind {
+    Point,
+    MultiPoint,
+    PolyLine,
+    Polygon,
+}
+
+impl ShapeKind {
+    const ALL: [Self; 4] = [Self::Point, Self::MultiPoint, Self::PolyLine, Self::Polygon];
+
+    fn code(self) -> i32 {
+        match self {
+            Self::Point => 1,
+            Self::PolyLine => 3,
+            Self::Polygon => 5,
+            Self::MultiPoint => 8,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Point => "point",
+            Self::MultiPoint => "multipoint",
+            Self::PolyLine => "line",
+            Self::Polygon => "polygon",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&kind| kind == self).unwrap()
+    }
+}
+
+/// 几何对象的维度：点 0、线 1、面 2，空几何集合为 `None`
+fn dimension(geometry: &Geometry) -> Option<u8> {
+    match geometry {
+        Geometry::Point { .. } | Geometry::MultiPoint { .. } => Some(0),
+        Geometry::LineString { .. } | Geometry::MultiLineString { .. } => Some(1),
+        Geometry::Polygon { .. } | Geometry::MultiPolygon { .. } => Some(2),
+        Geometry::GeometryCollection { geometries } => {
+            geometries.iter().filter_map(dimension).max()
+        }
+    }
+}
+
+/// 几何对象对应的 Shapefile 图层；几何集合只保留维度最高的部分
+fn shape_kind(geometry: &Geometry) -> ShapeKind {
+    match (geometry, dimension(geometry)) {
+        (Geometry::Point { .. }, _) => ShapeKind::Point,
+        (_, Some(2)) => ShapeKind::Polygon,
+        (_, Some(1)) => ShapeKind::PolyLine,
+        (Geometry::GeometryCollection { .. }, None) => ShapeKind::Point,
+        _ => ShapeKind::MultiPoint,
+    }
+}
+
+type Part = Vec<(f64, f64)>;
+
+/// 收集指定维度的部件；面的外环调整为顺时针，内环为逆时针
+fn collect_parts(geometry: &Geometry, dim: u8, parts: &mut Vec<Part>) {
+    fn points(positions: &[Position]) -> Part {
+        positions
+            .iter()
+            .filter_map(|p| match p.as_slice() {
+                [x, y, ..] => Some((*x, *y)),
+                _ => None,
+            })
+            .collect()
+    }
+    fn polygon(rings: &[Vec<Position>], parts: &mut Vec<Part>) {
+        for (i, ring) in rings.iter().enumerate() {
+            let mut ring = points(ring);
+            let area: f64 = ring
+                .windows(2)
+                .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+                .sum();
+            // 有向面积为正表示逆时针
+            if (i == 0) == (area > 0.0) {
+                ring.reverse();
+            }
+            parts.push(ring);
+        }
+    }
+
+    match (geometry, dim) {
+        (Geometry::Point { coordinates }, 0) => {
+            parts.push(points(std::slice::from_ref(coordinates)))
+        }
+        (Geometry::MultiPoint { coordinates }, 0) => parts.push(points(coordinates)),
+        (Geometry::LineString { coordinates }, 1) => parts.push(points(coordinates)),
+        (Geometry::MultiLineString { coordinates }, 1) => {
+            parts.extend(coordinates.iter().map(|line| points(line)))
+        }
+        (Geometry::Polygon { coordinates }, 2) => polygon(coordinates, parts),
+        (Geometry::MultiPolygon { coordinates }, 2) => {
+            coordinates.iter().for_each(|rings| polygon(rings, parts))
+        }
+        (Geometry::GeometryCollection { geometries }, _) => geometries
+            .iter()
+            .for_each(|geometry| collect_parts(geometry, dim, parts)),
+        _ => {}
+    }
+}
+
+/// 编码 `.shp` 记录内容（不含记录头），返回内容和外包框
+fn shape_content(geometry: &Geometry, kind: ShapeKind) -> (Vec<u8>, Option<[f64; 4]>) {
+    let dim = match kind {
+        ShapeKind::Point | ShapeKind::MultiPoint => 0,
+        ShapeKind::PolyLine => 1,
+        ShapeKind::Polygon => 2,
+    };
+    let mut parts = Vec::new();
+    collect_parts(geometry, dim, &mut parts);
+    parts.retain(|part| !part.is_empty());
+    let all: Vec<(f64, f64)> = parts.iter().flatten().copied().collect();
+
+    let mut out = Vec::new();
+    let Some(&(x, y)) = all.first() else {
+        // 空几何写为空形状
+        out.extend(0i32.to_le_bytes());
+        return (out, None);
+    };
+    let bbox = all.iter().fold([x, y, x, y], |[x0, y0, x1, y1], &(x, y)| {
+        [x0.min(x), y0.min(y), x1.max(x), y1.max(y)]
+    });
+
+    out.extend(kind.code().to_le_bytes());
+    if kind == ShapeKind::Point {
+        out.extend(x.to_le_bytes());
+        out.extend(y.to_le_bytes());
+        return (out, Some(bbox));
+    }
+    bbox.iter().for_each(|v| out.extend(v.to_le_bytes()));
+    if kind != ShapeKind::MultiPoint {
+        out.extend((parts.len() as i32).to_le_bytes());
+    }
+    out.extend((all.len() as i32).to_le_bytes());
+    if kind != ShapeKind::MultiPoint {
+        let mut start = 0i32;
+        for part in &parts {
+            out.extend(start.to_le_bytes());
+            start += part.len() as i32;
+        }
+    }
+    for (x, y) in all {
+        out.extend(x.to_le_bytes());
+        out.extend(y.to_le_bytes());
+    }
+    (out, Some(bbox))
+}
+
+/// 写出 `.shp`/`.shx` 的 100 字节文件头
+fn write_main_header(
+    out: &mut impl Write,
+    file_len: u64,
+    kind: ShapeKind,
+    bbox: Option<[f64; 4]>,
+) -> std::io::Result<()> {
+    out.write_all(&9994i32.to_be_bytes())?;
+    out.write_all(&[0; 20])?;
+    out.write_all(&((file_len / 2) as i32).to_be_bytes())?;
+    out.write_all(&1000i32.to_le_bytes())?;
+    out.write_all(&kind.code().to_le_bytes())?;
+    for v in bbox.unwrap_or_default() {
+        out.write_all(&v.to_le_bytes())?;
+    }
+    out.write_all(&[0; 32])
+}
+
+/// 写出 DBF 文件头和字段描述
+fn write_dbf_header(
+    out: &mut impl Write,
+    record_count: u32,
+    encoding: DbfEncoding,
+) -> std::io::Result<()> {
+    let field_count = TEXT_FIELDS.len() + NUMERIC_FIELDS.len();
+    let header_len = 32 + 32 * field_count + 1;
+    let (year, month, day) = time::civil_date(time::today());
+    out.write_all(&[0x03, (year - 1900) as u8, month, day])?;
+    out.write_all(&record_count.to_le_bytes())?;
+    out.write_all(&(header_len as u16).to_le_bytes())?;
+    out.write_all(&(dbf_record_len() as u16).to_le_bytes())?;
+    let mut reserved = [0u8; 20];
+    reserved[17] = encoding.language_driver();
+    out.write_all(&reserved)?;
+
+    let text = TEXT_FIELDS
+        .iter()
+        .map(|&(name, _, len)| (name, b'C', len, 0));
+    let numeric = NUMERIC_FIELDS
+        .iter()
+        .map(|&(name, len, decimals)| (name, b'N', len, decimals));
+    for (name, kind, len, decimals) in text.chain(numeric) {
+        let mut descriptor = [0u8; 32];
+        descriptor[..name.len()].copy_from_slice(name.as_bytes());
+        descriptor[11] = kind;
+        descriptor[16] = len as u8;
+        descriptor[17] = decimals as u8;
+        out.write_all(&descriptor)?;
+    }
+    out.write_all(&[0x0D])
+}
+
+/// DBF 记录长度（含删除标记）
+fn dbf_record_len() -> usize {
+    1 + TEXT_FIELDS.iter().map(|f| f.2).sum::<usize>()
+        + NUMERIC_FIELDS.iter().map(|f| f.1).sum::<usize>()
+}
+
+/// 单个几何类型的图层
+struct Layer {
+    kind: ShapeKind,
+    shp: BufWriter<File>,
+    shx: BufWriter<File>,
+    dbf: BufWriter<File>,
+    /// `.shp` 当前长度（字节）
+    shp_len: u64,
+    count: u32,
+    bbox: Option<[f64; 4]>,
+}
+
+impl Layer {
+    fn create(base: &Path, kind: ShapeKind, encoding: DbfEncoding, crs: Crs) -> Result<Self> {
+        let path = |ext: &str| layer_path(base, kind, ext);
+        std::fs::write(path("prj"), crs.esri_wkt())?;
+        std::fs::write(path("cpg"), encoding.code_page())?;
+        let mut shp = BufWriter::new(File::create(path("shp"))?);
+        let mut shx = BufWriter::new(File::create(path("shx"))?);
+        let mut dbf = BufWriter::new(File::create(path("dbf"))?);
+        // 文件头先占位，结束时回填长度、外包框和记录数
+        write_main_header(&mut shp, 100, kind, None)?;
+        write_main_header(&mut shx, 100, kind, None)?;
+        write_dbf_header(&mut dbf, 0, encoding)?;
+        Ok(Self {
+            kind,
+            shp,
+            shx,
+            dbf,
+            shp_len: 100,
+            count: 0,
+            bbox: None,
+        })
+    }
+
+    fn write(&mut self, geometry: &Geometry, attributes: &[u8]) -> Result<()> {
+        let (content, bbox) = shape_content(geometry, self.kind);
+        self.count += 1;
+        let content_words = (content.len() / 2) as i32;
+        self.shx
+            .write_all(&((self.shp_len / 2) as i32).to_be_bytes())?;
+        self.shx.write_all(&content_words.to_be_bytes())?;
+        self.shp.write_all(&(self.count as i32).to_be_bytes())?;
+        self.shp.write_all(&content_words.to_be_bytes())?;
+        self.shp.write_all(&content)?;
+        self.shp_len += 8 + content.len() as u64;
+        self.dbf.write_all(attributes)?;
+        if let Some([x0, y0, x1, y1]) = bbox {
+            self.bbox = Some(match self.bbox {
+                None => [x0, y0, x1, y1],
+                Some([a0, b0, a1, b1]) => [a0.min(x0), b0.min(y0), a1.max(x1), b1.max(y1)],
+            });
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.shp.flush()?;
+        self.shx.flush()?;
+        self.dbf.flush()?;
+        Ok(())
+    }
+
+    fn finish(mut self, encoding: DbfEncoding) -> Result<()> {
+        self.dbf.write_all(&[0x1A])?;
+        let shx_len = 100 + 8 * u64::from(self.count);
+        self.shp.seek(SeekFrom::Start(0))?;
+        write_main_header(&mut self.shp, self.shp_len, self.kind, self.bbox)?;
+        self.shx.seek(SeekFrom::Start(0))?;
+        write_main_header(&mut self.shx, shx_len, self.kind, self.bbox)?;
+        self.dbf.seek(SeekFrom::Start(0))?;
+        write_dbf_header(&mut self.dbf, self.count, encoding)?;
+        self.flush()
+    }
+}
+
+/// 图层文件路径 `<名称>_<类型>.<扩展名>`
+fn layer_path(base: &Path, kind: ShapeKind, ext: &str) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    base.with_file_name(format!("{}_{}.{}", stem, kind.suffix(), ext))
+}
+
+/// Shapefile 导出器
+///
+/// 结束时必须调用 [`finish`](Self::finish) 回填文件头，否则生成的文件无法打开。
+pub struct ShapefileWriter {
+    base: PathBuf,
+    options: ShapefileOptions,
+    layers: [Option<Layer>; 4],
+    sidecar: Option<::csv::Writer<File>>,
+}
+
+impl ShapefileWriter {
+    /// 以默认选项创建导出器，`path` 为不含几何类型后缀的文件名，如 `out/settlements.shp`
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_options(path, ShapefileOptions::default())
+    }
+
+    /// 以指定选项创建导出器
+    pub fn with_options(path: impl AsRef<Path>, options: ShapefileOptions) -> Result<Self> {
+        let base = path.as_ref().to_path_buf();
+        if base.file_stem().is_none() {
+            return Err(Error::Config(format!(
+                "无效的 Shapefile 路径: {}",
+                base.display()
+            )));
+        }
+        Ok(Self {
+            base,
+            options,
+            layers: Default::default(),
+            sidecar: None,
+        })
+    }
+
+    /// 写入一条地名详情
+    pub fn write(&mut self, details: &DetailsQueryResponse) -> Result<()> {
+        let details = reproject(details, self.options.crs);
+        let record = FlatRecord::from(details.as_ref());
+        let attributes = self.encode_attributes(&record)?;
+        let kind = shape_kind(&details.gdm);
+        let options = self.options;
+        let layer = match &mut self.layers[kind.index()] {
+            Some(layer) => layer,
+            slot => slot.insert(Layer::create(
+                &self.base,
+                kind,
+                options.encoding,
+                options.crs,
+            )?),
+        };
+        layer.write(&details.gdm, &attributes)
+    }
+
+    /// 已写入的要素数
+    pub fn count(&self) -> usize {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|layer| layer.count as usize)
+            .sum()
+    }
+
+    /// 已创建的 `.shp` 文件
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|layer| layer_path(&self.base, layer.kind, "shp"))
+            .collect()
+    }
+
+    /// 把缓冲区写入文件
+    pub fn flush(&mut self) -> Result<()> {
+        for layer in self.layers.iter_mut().flatten() {
+            layer.flush()?;
+        }
+        if let Some(sidecar) = &mut self.sidecar {
+            sidecar.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 回填文件头并关闭全部文件
+    pub fn finish(self) -> Result<()> {
+        for layer in self.layers.into_iter().flatten() {
+            layer.finish(self.options.encoding)?;
+        }
+        if let Some(mut sidecar) = self.sidecar {
+            sidecar.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 编码一条 DBF 记录，超长文本按选项处理
+    fn encode_attributes(&mut self, record: &FlatRecord) -> Result<Vec<u8>> {
+        let texts = [
+            Some(record.id.as_str()),
+            Some(record.standard_name.as_str()),
+            Some(record.roman_alphabet_spelling.as_str()),
+            Some(record.place_type.as_str()),
+            Some(record.place_type_code.as_str()),
+            Some(record.place_code.as_str()),
+            Some(record.province.as_str()),
+            Some(record.province_name.as_str()),
+            record.city.as_deref(),
+            record.city_name.as_deref(),
+            record.area.as_deref(),
+            record.area_name.as_deref(),
+            record.old_name.as_deref(),
+            Some(record.ethnic_minorities_writing.as_str()),
+            Some(record.place_meaning.as_str()),
+            Some(record.place_origin.as_str()),
+            record.government_history.as_deref(),
+        ];
+
+        let mut out = Vec::with_capacity(dbf_record_len());
+        out.push(b' ');
+        for (&(_, field, len), text) in TEXT_FIELDS.iter().zip(texts) {
+            let text = text.unwrap_or_default();
+            let (mut bytes, truncated) = self.options.encoding.encode_truncated(text, len);
+            if truncated && self.options.long_text == LongTextPolicy::Sidecar {
+                self.sidecar()?
+                    .write_record([record.id.as_str(), field, text])?;
+            }
+            bytes.resize(len, b' ');
+            out.extend(bytes);
+        }
+        for (&(name, len, decimals), value) in NUMERIC_FIELDS.iter().zip([record.lon, record.lat]) {
+            let text = value
+                .filter(|v| v.is_finite())
+                .map(|v| format!("{:>len$.decimals$}", v))
+                .unwrap_or_default();
+            // 截断数值会得到错误的坐标，超宽时报错
+            if text.len() > len {
+                return Err(Error::Config(format!(
+                    "{} 的 {} 值 {} 超出 DBF 字段宽度 {}",
+                    record.id, name, text, len
+                )));
+            }
+            out.extend(format!("{:>len$}", text).bytes());
+        }
+        Ok(out)
+    }
+
+    /// 取得（必要时创建）超长文本旁路表
+    fn sidecar(&mut self) -> Result<&mut ::csv::Writer<File>> {
+        if self.sidecar.is_none() {
+            let stem = self.base.file_stem().unwrap_or_default().to_string_lossy();
+            let path = self.base.with_file_name(format!("{}_long_text.csv", stem));
+            let mut writer = ::csv::Writer::from_path(path)?;
+            writer.write_record(["id", "field", "value"])?;
+            self.sidecar = Some(writer);
+        }
+        Ok(self.sidecar.as_mut().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+
+    fn read_i32_be(bytes: &[u8], at: usize) -> i32 {
+        i32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn read_i32_le(bytes: &[u8], at: usize) -> i32 {
+        i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_point_layer_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ShapefileWriter::create(dir.path().join("settlements.shp")).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.write(&sample_details()).unwrap();
+        assert_eq!(writer.count(), 2);
+        assert_eq!(
+            writer.files(),
+            vec![dir.path().join("settlements_point.shp")]
+        );
+        writer.finish().unwrap();
+
+        let shp = std::fs::read(dir.path().join("settlements_point.shp")).unwrap();
+        assert_eq!(read_i32_be(&shp, 0), 9994);
+        assert_eq!(read_i32_be(&shp, 24) as usize * 2, shp.len());
+        assert_eq!(read_i32_le(&shp, 32), 1);
+        assert_eq!(shp.len(), 100 + 2 * (8 + 20));
+
+        let shx = std::fs::read(dir.path().join("settlements_point.shx")).unwrap();
+        assert_eq!(shx.len(), 100 + 2 * 8);
+        assert_eq!(read_i32_be(&shx, 108), (100 + 28) / 2);
+
+        let dbf = std::fs::read(dir.path().join("settlements_point.dbf")).unwrap();
+        assert_eq!(u32::from_le_bytes(dbf[4..8].try_into().unwrap()), 2);
+        let header_len = u16::from_le_bytes(dbf[8..10].try_into().unwrap()) as usize;
+        assert_eq!(dbf.len(), header_len + 2 * dbf_record_len() + 1);
+        // GBK 编码的“唐庄村”紧跟在 64 字节的 id 字段之后
+        let name = &dbf[header_len + 1 + 64..header_len + 1 + 64 + 6];
+        assert_eq!(name, encoding_rs::GBK.encode("唐庄村").0.as_ref());
+
+        let cpg = std::fs::read_to_string(dir.path().join("settlements_point.cpg")).unwrap();
+        assert_eq!(cpg, "GBK");
+        assert!(dir.path().join("settlements_point.prj").exists());
+    }
+
+    #[test]
+    fn test_polygon_orientation_and_long_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ShapefileOptions::default().encoding(DbfEncoding::Utf8);
+        let mut writer = ShapefileWriter::with_options(dir.path().join("areas"), options).unwrap();
+        // 逆时针外环，写入时应调整为顺时针
+        let mut details = sample_details_with(
+            "poly",
+            r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#,
+        );
+        details.place_origin = "唐".repeat(100);
+        writer.write(&details).unwrap();
+        writer.finish().unwrap();
+
+        let shp = std::fs::read(dir.path().join("areas_polygon.shp")).unwrap();
+        assert_eq!(read_i32_le(&shp, 32), 5);
+        // 记录头 8 字节 + 类型 4 + 外包框 32 + 部件数 4 + 点数 4 + 部件索引 4
+        let points = 100 + 8 + 4 + 32 + 4 + 4 + 4;
+        let xy = |i: usize| {
+            let at = points + i * 16;
+            (
+                f64::from_le_bytes(shp[at..at + 8].try_into().unwrap()),
+                f64::from_le_bytes(shp[at + 8..at + 16].try_into().unwrap()),
+            )
+        };
+        assert_eq!(xy(1), (1.0, 1.0));
+
+        let sidecar = std::fs::read_to_string(dir.path().join("areas_long_text.csv")).unwrap();
+        let lines: Vec<&str> = sidecar.lines().collect();
+        assert_eq!(lines[0], "id,field,value");
+        assert!(lines[1].starts_with("poly,place_origin,唐唐"));
+    }
+
+    #[test]
+    fn test_projected_coordinates_fit_numeric_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ShapefileOptions::default().crs(Crs::gauss_kruger_3(38));
+        let mut writer =
+            ShapefileWriter::with_options(dir.path().join("settlements"), options).unwrap();
+        writer.write(&sample_details()).unwrap();
+        writer.finish().unwrap();
+
+        let dbf = std::fs::read(dir.path().join("settlements_point.dbf")).unwrap();
+        let header_len = u16::from_le_bytes(dbf[8..10].try_into().unwrap()) as usize;
+        let lon_at = header_len + 1 + TEXT_FIELDS.iter().map(|f| f.2).sum::<usize>();
+        let lon = std::str::from_utf8(&dbf[lon_at..lon_at + NUMERIC_FIELDS[0].1]).unwrap();
+        // 带号 38 的横坐标有 8 位整数
+        let lon: f64 = lon.trim().parse().unwrap();
+        assert!((38_000_000.0..39_000_000.0).contains(&lon), "{}", lon);
+    }
+
+    #[test]
+    fn test_numeric_overflow_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ShapefileWriter::create(dir.path().join("settlements")).unwrap();
+        let details = sample_details_with("far", r#"{"type":"Point","coordinates":[1e20,0]}"#);
+        assert!(matches!(writer.write(&details), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_truncate_keeps_whole_characters() {
+        let (bytes, truncated) = DbfEncoding::Utf8.encode_truncated("唐庄村", 7);
+        assert!(truncated);
+        assert_eq!(bytes, "唐庄".as_bytes());
+        let (bytes, truncated) = DbfEncoding::Gbk.encode_truncated("唐庄村", 6);
+        assert!(!truncated);
+        assert_eq!(bytes.len(), 6);
+    }
+}
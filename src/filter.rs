@@ -8,44 +8,52 @@ pub async fn counties(code: &str) -> io::Result<()> {
     let province = cli
         .division(code, QueryLevel::GrandChild)
         .await
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        .map_err(io::Error::other)?;
 
     // 创建文件用于保存县级代码
     let mut output_file = File::create("county_codes.txt").await?;
     let mut count = 0;
 
+    for (_, county) in county_divisions(&province) {
+        // 直接写入县级代码的前六位
+        if let Some(code_prefix) = get_code_prefix(&county.code) {
+            output_file
+                .write_all(format!("{}\n", code_prefix).as_bytes())
+                .await?;
+            count += 1;
+        }
+    }
+
+    // 确保所有数据都写入文件
+    output_file.flush().await?;
+
+    println!("Total county codes: {}", count);
+    Ok(())
+}
+
+/// 省级行政区下的县和县级市（不含市辖区），返回 `(上级区划, 县级行政区)`
+///
+/// 省直辖县级市没有下级区划，上级区划为省
+pub fn county_divisions(
+    province: &DivisonQueryResult,
+) -> Vec<(&DivisonQueryResult, &DivisonQueryResult)> {
+    let mut counties = Vec::new();
     // 处理所有地级市
     for city in &province.children {
         if city.children.is_empty() {
-            // 省直辖县级市，直接存储前六位
-            if let Some(code_prefix) = get_code_prefix(&city.code) {
-                output_file
-                    .write_all(format!("{}\n", code_prefix).as_bytes())
-                    .await?;
-                count += 1;
-            }
+            // 省直辖县级市
+            counties.push((province, city));
             continue;
         }
 
         // 处理地级市下的县级区域
         for county in &city.children {
             if county.r#type == "县" || county.r#type == "县级市" {
-                // 直接写入县级代码的前六位
-                if let Some(code_prefix) = get_code_prefix(&county.code) {
-                    output_file
-                        .write_all(format!("{}\n", code_prefix).as_bytes())
-                        .await?;
-                    count += 1;
-                }
+                counties.push((city, county));
             }
         }
     }
-
-    // 确保所有数据都写入文件
-    output_file.flush().await?;
-
-    println!("Total county codes: {}", count);
-    Ok(())
+    counties
 }
 
 /// 获取行政代码的前六位
@@ -60,6 +68,25 @@ fn get_code_prefix(code: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::sample_division;
+
+    #[test]
+    fn test_county_divisions() {
+        let province = sample_division();
+        let codes: Vec<(&str, &str)> = county_divisions(&province)
+            .into_iter()
+            .map(|(parent, county)| (parent.code.as_str(), county.code.as_str()))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                ("410100000000", "410122000000"),
+                ("410100000000", "410181000000"),
+                ("410200000000", "410225000000"),
+                ("410000000000", "419001000000"),
+            ]
+        );
+    }
 
     #[tokio::test]
     #[ignore]
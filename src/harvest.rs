@@ -0,0 +1,1513 @@
+//! # 地名采集
+//!
+//! [`Harvester`] 按县级行政区逐个搜索指定类别的地名，再逐条获取详细信息，
+//! 写入 CSV、GeoJSON、GeoPackage 和 SQLite 数据库。要采集的类别由 [`PlaceTypeRule`]
+//! 描述，每个类别可以有自己的筛选规则；采集范围由 [`Region`] 描述。
+//!
+//! 采集过程可以中断后继续：进度记录在输出目录下的 [`CHECKPOINT_FILE`]，
+//! 失败的搜索和详情请求记录在 `failed_requests.json`，全部县处理完后统一重试。
+
+use crate::api::*;
+use crate::error::Result;
+use crate::export::{CsvExporter, ExportFormat, GeoJsonWriter, GeoPackageWriter};
+use crate::storage::{Failure, FailureKind, RunId, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 采集结果的 SQLite 数据库文件名，位于输出目录下
+pub const STORAGE_FILE: &str = "toponyms.sqlite";
+
+/// 采集进度文件名，位于输出目录下
+pub const CHECKPOINT_FILE: &str = "checkpoint.ndjson";
+
+/// 失败请求记录文件名，位于输出目录下
+pub const FAILED_REQUESTS_FILE: &str = "failed_requests.json";
+
+/// 重试后仍失败的请求记录文件名，位于输出目录下
+pub const FINAL_FAILED_REQUESTS_FILE: &str = "final_failed_requests.json";
+
+/// 默认最多重试的轮数
+pub const DEFAULT_RETRY_ROUNDS: u32 = 5;
+
+/// 默认单个请求最多重试的次数
+pub const DEFAULT_REQUEST_RETRIES: u32 = 3;
+
+/// 每写入多少条地名详情把输出文件落盘一次
+const FLUSH_INTERVAL: usize = 100;
+
+/// 农村居民点的类别代码
+const RURAL_SETTLEMENTS_CODE: &str = "22200";
+
+/// 地名类别的筛选规则
+///
+/// 搜索结果的类别代码必须与 `code` 一致（或在允许时属于其子类别）；
+/// 设置了类别名称或名称关键字时，还需类别名称相同或标准名称包含任一关键字。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlaceTypeRule {
+    code: String,
+    #[serde(default)]
+    place_type: Option<String>,
+    #[serde(default)]
+    name_keywords: Vec<String>,
+    #[serde(default)]
+    include_sub_types: bool,
+}
+
+impl PlaceTypeRule {
+    /// 采集指定类别代码的全部地名
+    pub fn new(code: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            place_type: None,
+            name_keywords: Vec::new(),
+            include_sub_types: false,
+        }
+    }
+
+    /// 农村居民点（类别代码 22200）
+    ///
+    /// 类别名称为 "农村居民点"，或标记不完整但名称包含 "村" 的地名
+    pub fn rural_settlements() -> Self {
+        Self::new(RURAL_SETTLEMENTS_CODE)
+            .place_type("农村居民点")
+            .name_keyword("村")
+    }
+
+    /// 要求类别名称一致
+    pub fn place_type(mut self, place_type: impl Into<String>) -> Self {
+        self.place_type = Some(place_type.into());
+        self
+    }
+
+    /// 类别名称不一致时，标准名称包含此关键字也视为符合
+    pub fn name_keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.name_keywords.push(keyword.into());
+        self
+    }
+
+    /// 同时接受子类别，例如 `24000` 接受 `24100`、`24110`
+    pub fn include_sub_types(mut self, include: bool) -> Self {
+        self.include_sub_types = include;
+        self
+    }
+
+    /// 类别代码
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// 用于输出信息的名称：有类别名称时使用类别名称，否则使用类别代码
+    pub fn label(&self) -> &str {
+        self.place_type.as_deref().unwrap_or(&self.code)
+    }
+
+    /// 搜索结果是否符合规则
+    pub fn matches(&self, record: &Record) -> bool {
+        let Some(code) = record.place_type_code.as_deref() else {
+            return false;
+        };
+        let code_matches = if self.include_sub_types {
+            code.starts_with(self.code.trim_end_matches('0'))
+        } else {
+            code == self.code
+        };
+        if !code_matches {
+            return false;
+        }
+        if self.place_type.is_none() && self.name_keywords.is_empty() {
+            return true;
+        }
+        self.place_type.as_deref() == Some(record.place_type.as_str())
+            || self
+                .name_keywords
+                .iter()
+                .any(|keyword| record.standard_name.contains(keyword.as_str()))
+    }
+}
+
+/// 采集范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    /// 省、市或县级行政区划代码，采集其下全部县级行政区（包括市辖区）
+    Division(String),
+    /// 县级行政区划代码列表
+    Counties(Vec<String>),
+}
+
+impl Region {
+    /// 省、市或县级行政区
+    pub fn division(code: impl Into<String>) -> Self {
+        Self::Division(code.into())
+    }
+
+    /// 县级行政区划代码列表
+    pub fn counties<I, S>(codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Counties(codes.into_iter().map(Into::into).collect())
+    }
+
+    /// 读取每行一个县级行政区划代码的文件，例如 [`counties`](crate::filter::counties) 生成的 `county_codes.txt`
+    pub fn from_county_file(path: impl AsRef<Path>) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::counties(
+            text.lines().map(str::trim).filter(|line| !line.is_empty()),
+        ))
+    }
+
+    /// 展开为县级行政区划代码（前六位），行政区需要通过接口查询 `year` 年版（默认最新年版）的下级区划
+    pub async fn county_codes(&self, cli: &Cli, year: Option<i32>) -> Result<Vec<String>> {
+        let code = match self {
+            Self::Counties(codes) => return Ok(codes.clone()),
+            Self::Division(code) => code,
+        };
+        if is_county_code(code) {
+            return Ok(vec![code[..6].to_string()]);
+        }
+        let params = match year {
+            Some(year) => DivisionQueryParams::new(year, code.clone(), QueryLevel::GrandChild),
+            None => DivisionQueryParams::latest(code, QueryLevel::GrandChild),
+        };
+        let division = cli.execute(&params).await?.data;
+        let mut codes = Vec::new();
+        collect_counties(&division, &mut codes);
+        Ok(codes)
+    }
+}
+
+/// 采集时可以输出的格式，中断后能接着追加；SQLite 数据库总会写入。
+/// 其他格式可以在采集后用 [`ExportFormat::export`] 从数据库导出
+pub const HARVEST_FORMATS: [ExportFormat; 3] = [
+    ExportFormat::Csv,
+    ExportFormat::GeoJson,
+    ExportFormat::GeoPackage,
+];
+
+/// 检查输出格式是否都能在采集时写入
+fn check_harvest_formats(formats: &[ExportFormat]) -> Result<()> {
+    match formats
+        .iter()
+        .find(|format| !HARVEST_FORMATS.contains(format))
+    {
+        Some(format) => Err(crate::Error::Config(format!(
+            "采集不能输出 {} 格式（只支持 csv、geojson 和 gpkg）",
+            format
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 是否为县级行政区划代码：第 5、6 位不为 0，其后全部为 0
+fn is_county_code(code: &str) -> bool {
+    code.len() >= 6
+        && code.is_ascii()
+        && &code[4..6] != "00"
+        && code[6..].bytes().all(|b| b == b'0')
+}
+
+fn collect_counties(division: &DivisonQueryResult, codes: &mut Vec<String>) {
+    if is_county_code(&division.code) {
+        let code = division.code[..6].to_string();
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+        return;
+    }
+    for child in &division.children {
+        collect_counties(child, codes);
+    }
+}
+
+/// 失败请求的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RequestKind {
+    /// 县级行政区搜索，`id` 为县级行政区划代码
+    Search,
+    /// 地名详情，`id` 为地名ID
+    #[default]
+    Details,
+}
+
+/// 失败的请求记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FailedRequest {
+    /// 旧版本的记录中没有此字段，均为地名详情请求
+    #[serde(default)]
+    kind: RequestKind,
+    id: String,
+    name: String,
+    county_code: String,
+    /// 搜索的地名类别代码，旧版本的记录中没有此字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    place_type_code: Option<String>,
+    retry_count: u32,
+}
+
+impl FailedRequest {
+    /// 县级行政区搜索失败
+    fn search(county_code: &str, rule: &PlaceTypeRule) -> Self {
+        Self {
+            kind: RequestKind::Search,
+            id: county_code.to_string(),
+            name: String::new(),
+            county_code: county_code.to_string(),
+            place_type_code: Some(rule.code.clone()),
+            retry_count: 0,
+        }
+    }
+
+    /// 地名详情获取失败
+    fn details(record: &Record, county_code: &str) -> Self {
+        Self {
+            kind: RequestKind::Details,
+            id: record.id.clone(),
+            name: record.standard_name.clone(),
+            county_code: county_code.to_string(),
+            place_type_code: record.place_type_code.clone(),
+            retry_count: 0,
+        }
+    }
+
+    /// 去重用的键：同一县的不同类别搜索是不同的请求
+    fn key(&self) -> (RequestKind, String, Option<String>) {
+        let code = match self.kind {
+            RequestKind::Search => self.place_type_code.clone(),
+            RequestKind::Details => None,
+        };
+        (self.kind, self.id.clone(), code)
+    }
+
+    /// 转换为数据库中的失败记录
+    fn to_failure(&self, error: &str) -> Failure {
+        let (kind, target, name) = match self.kind {
+            RequestKind::Search => (
+                FailureKind::Search,
+                match &self.place_type_code {
+                    Some(code) => format!("{}:{}", self.id, code),
+                    None => self.id.clone(),
+                },
+                None,
+            ),
+            RequestKind::Details => (
+                FailureKind::Details,
+                self.id.clone(),
+                Some(self.name.clone()),
+            ),
+        };
+        Failure {
+            kind,
+            target,
+            name,
+            county_code: Some(self.county_code.clone()),
+            error: error.to_string(),
+            retry_count: self.retry_count,
+        }
+    }
+}
+
+impl std::fmt::Display for FailedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.kind, &self.place_type_code) {
+            (RequestKind::Search, Some(code)) => {
+                write!(f, "搜索县级行政区 {}（类别 {}）", self.id, code)
+            }
+            (RequestKind::Search, None) => write!(f, "搜索县级行政区 {}", self.id),
+            (RequestKind::Details, _) => write!(f, "{} ({})", self.name, self.id),
+        }
+    }
+}
+
+/// 读取失败请求记录，空文件视为没有失败的请求
+async fn load_failed_requests(path: &Path) -> Result<Vec<FailedRequest>> {
+    let json = tokio::fs::read_to_string(path).await?;
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&json).map_err(|source| crate::Error::Decode { source, body: json })
+}
+
+/// 保存失败请求记录
+async fn save_failed_requests(path: &Path, requests: &[FailedRequest]) -> Result<()> {
+    let json = serde_json::to_string_pretty(requests).map_err(std::io::Error::from)?;
+    tokio::fs::write(path, json).await?;
+    Ok(())
+}
+
+/// 采集进度中的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CheckpointEntry {
+    /// 之后完成的县使用的地名类别
+    Rules { rules: Vec<PlaceTypeRule> },
+    /// 县级行政区已处理完
+    County { code: String },
+    /// 地名详情已写入输出文件
+    Details { id: String },
+}
+
+/// 采集进度
+///
+/// 每完成一个县或写入一条详情就向进度文件追加一行，中断后重新运行时跳过已完成的工作。
+/// 只追加不改写，即使在写入时中断也最多丢失最后一行。
+///
+/// 县的完成记录与当时的地名类别相关：类别改变后追加一条新的 `rules` 记录，
+/// 之前完成的县按新的类别重新搜索，已写入的地名详情不再重复获取。
+struct Checkpoint {
+    path: PathBuf,
+    file: std::fs::File,
+    completed_counties: HashSet<String>,
+    fetched_ids: HashSet<String>,
+}
+
+impl Checkpoint {
+    /// 打开（必要时创建）进度文件并读取使用 `rules` 时已完成的工作
+    fn open(path: impl AsRef<Path>, rules: &[PlaceTypeRule]) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut completed_counties = HashSet::new();
+        let mut fetched_ids = HashSet::new();
+        let mut complete_len = 0;
+        // 旧版本的进度没有 rules 记录，只采集农村居民点
+        let mut recorded_rules = vec![PlaceTypeRule::rural_settlements()];
+        if path.exists() {
+            let data = std::fs::read_to_string(&path)?;
+            // 中断时没有写完的最后一行没有换行符，丢弃
+            complete_len = data.rfind('\n').map_or(0, |i| i + 1);
+            for line in data[..complete_len].lines() {
+                match serde_json::from_str(line) {
+                    Ok(CheckpointEntry::Rules { rules }) => {
+                        recorded_rules = rules;
+                    }
+                    Ok(CheckpointEntry::County { code }) => {
+                        if same_rules(&recorded_rules, rules) {
+                            completed_counties.insert(code);
+                        }
+                    }
+                    Ok(CheckpointEntry::Details { id }) => {
+                        fetched_ids.insert(id);
+                    }
+                    Err(e) => eprintln!("忽略无法解析的采集进度: {}", e),
+                }
+            }
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        file.set_len(complete_len as u64)?;
+        let mut checkpoint = Self {
+            path,
+            file,
+            completed_counties,
+            fetched_ids,
+        };
+        if !same_rules(&recorded_rules, rules) {
+            checkpoint.append(&CheckpointEntry::Rules {
+                rules: rules.to_vec(),
+            })?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// 是否有之前的采集进度
+    fn is_resuming(&self) -> bool {
+        !self.completed_counties.is_empty() || !self.fetched_ids.is_empty()
+    }
+
+    fn is_county_completed(&self, code: &str) -> bool {
+        self.completed_counties.contains(code)
+    }
+
+    fn is_fetched(&self, id: &str) -> bool {
+        self.fetched_ids.contains(id)
+    }
+
+    /// 记录已完成的县
+    fn complete_county(&mut self, code: &str) -> Result<()> {
+        self.append(&CheckpointEntry::County {
+            code: code.to_string(),
+        })?;
+        self.completed_counties.insert(code.to_string());
+        Ok(())
+    }
+
+    /// 记录已写入的地名详情
+    fn record_fetched(&mut self, id: &str) -> Result<()> {
+        self.append(&CheckpointEntry::Details { id: id.to_string() })?;
+        self.fetched_ids.insert(id.to_string());
+        Ok(())
+    }
+
+    fn append(&mut self, entry: &CheckpointEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(std::io::Error::from)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// 采集全部完成后删除进度文件，下次运行重新开始
+    fn remove(self) -> Result<()> {
+        drop(self.file);
+        std::fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+/// 两组地名类别是否相同，不计顺序
+fn same_rules(a: &[PlaceTypeRule], b: &[PlaceTypeRule]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|rule| b.contains(rule))
+        && b.iter().all(|rule| a.contains(rule))
+}
+
+/// 采集结果的输出文件：选定格式的文件以及 SQLite 数据库
+///
+/// 同一地名只写入一次，重复写入时忽略。写入的数据先留在缓冲区（GeoPackage 为未提交的事务，
+/// 数据库为待写入的详情），由调用方按 [`needs_flush`](Self::needs_flush) 或在县的边界调用
+/// [`flush`](Self::flush) 落盘。
+pub(crate) struct HarvestSink {
+    csv: Option<CsvExporter<std::fs::File>>,
+    geojson: Option<GeoJsonWriter<std::io::BufWriter<std::fs::File>>>,
+    gpkg: Option<GeoPackageWriter>,
+    storage: Storage,
+    run: RunId,
+    /// 已写入的地名 ID
+    written: HashSet<String>,
+    /// 已写入输出文件、尚未存入数据库并落盘的地名详情
+    pending: Vec<DetailsQueryResponse>,
+}
+
+impl HarvestSink {
+    /// 在输出目录下创建 `formats` 对应的 `<name>.csv`、`<name>.geojson`、`<name>.gpkg`，
+    /// 并在 [`STORAGE_FILE`] 中开始一个新的采集批次
+    pub(crate) fn create(output_dir: &Path, name: &str, formats: &[ExportFormat]) -> Result<Self> {
+        check_harvest_formats(formats)?;
+        let storage = Storage::open(output_dir.join(STORAGE_FILE))?;
+        let run = storage.begin_run(name)?;
+        let path = |format: ExportFormat| {
+            formats
+                .contains(&format)
+                .then(|| output_path(output_dir, name, format))
+        };
+        Ok(Self {
+            csv: path(ExportFormat::Csv)
+                .map(CsvExporter::create)
+                .transpose()?,
+            geojson: path(ExportFormat::GeoJson)
+                .map(GeoJsonWriter::create)
+                .transpose()?,
+            gpkg: path(ExportFormat::GeoPackage)
+                .map(GeoPackageWriter::create)
+                .transpose()?,
+            storage,
+            run,
+            written: HashSet::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// 与 [`create`](Self::create) 相同，但追加到已有的输出文件，已有的地名不再重复写入
+    ///
+    /// 已写入的 ID 从 CSV 文件读取，不输出 CSV 时由调用方通过 [`mark_written`](Self::mark_written) 提供
+    pub(crate) fn append(output_dir: &Path, name: &str, formats: &[ExportFormat]) -> Result<Self> {
+        check_harvest_formats(formats)?;
+        let storage = Storage::open(output_dir.join(STORAGE_FILE))?;
+        let run = storage.begin_run(&format!("{}（续）", name))?;
+        let path = |format: ExportFormat| {
+            formats
+                .contains(&format)
+                .then(|| output_path(output_dir, name, format))
+        };
+        let mut written = HashSet::new();
+        let csv = match path(ExportFormat::Csv) {
+            Some(csv_path) => {
+                // 先打开 CSV 文件截掉没有写完的最后一行，再读取已写入的 ID
+                let csv = CsvExporter::append(&csv_path)?;
+                for record in ::csv::Reader::from_path(&csv_path)?.records() {
+                    if let Some(id) = record?.get(0) {
+                        written.insert(id.to_string());
+                    }
+                }
+                Some(csv)
+            }
+            None => None,
+        };
+        Ok(Self {
+            csv,
+            geojson: path(ExportFormat::GeoJson)
+                .map(GeoJsonWriter::append)
+                .transpose()?,
+            gpkg: path(ExportFormat::GeoPackage)
+                .map(GeoPackageWriter::open)
+                .transpose()?,
+            storage,
+            run,
+            written,
+            pending: Vec::new(),
+        })
+    }
+
+    /// 把这些地名视为已写入
+    fn mark_written<'a>(&mut self, ids: impl IntoIterator<Item = &'a String>) {
+        self.written.extend(ids.into_iter().cloned());
+    }
+
+    /// 保存搜索结果
+    pub(crate) fn write_records(&mut self, records: &[Record]) -> Result<()> {
+        self.storage.upsert_records(Some(self.run), records)
+    }
+
+    /// 写入一条地名详情，已写入过同一 ID 时忽略并返回 `false`
+    pub(crate) fn write(&mut self, details: &DetailsQueryResponse) -> Result<bool> {
+        if !self.written.insert(details.id.clone()) {
+            return Ok(false);
+        }
+        if let Some(csv) = &mut self.csv {
+            csv.write(details)?;
+        }
+        if let Some(geojson) = &mut self.geojson {
+            geojson.write(details)?;
+        }
+        if let Some(gpkg) = &mut self.gpkg {
+            gpkg.write(details)?;
+        }
+        self.pending.push(details.clone());
+        Ok(true)
+    }
+
+    /// 未落盘的地名是否已达到 [`FLUSH_INTERVAL`]
+    pub(crate) fn needs_flush(&self) -> bool {
+        self.pending.len() >= FLUSH_INTERVAL
+    }
+
+    /// 记录失败的请求
+    fn record_failure(&self, request: &FailedRequest, error: &str) -> Result<()> {
+        self.storage
+            .record_failure(Some(self.run), &request.to_failure(error))
+    }
+
+    /// 请求已成功，删除失败记录
+    fn resolve_failure(&self, request: &FailedRequest) -> Result<()> {
+        let failure = request.to_failure("");
+        self.storage.resolve_failure(failure.kind, &failure.target)
+    }
+
+    /// 把已写入的数据落盘并在一个事务中存入数据库，返回这次落盘的地名 ID
+    pub(crate) fn flush(&mut self) -> Result<Vec<String>> {
+        if let Some(csv) = &mut self.csv {
+            csv.flush()?;
+        }
+        if let Some(geojson) = &mut self.geojson {
+            geojson.flush()?;
+        }
+        if let Some(gpkg) = &mut self.gpkg {
+            gpkg.flush()?;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        self.storage.upsert_details(Some(self.run), &pending)?;
+        Ok(pending.into_iter().map(|details| details.id).collect())
+    }
+
+    /// 补全文件结尾并结束采集批次
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.flush()?;
+        if let Some(geojson) = self.geojson {
+            geojson.finish()?;
+        }
+        if let Some(gpkg) = self.gpkg {
+            gpkg.finish()?;
+        }
+        self.storage.finish_run(self.run, "finished")
+    }
+}
+
+/// 输出文件路径：`<output_dir>/<name>.<扩展名>`
+pub fn output_path(output_dir: &Path, name: &str, format: ExportFormat) -> PathBuf {
+    output_dir.join(format!("{}.{}", name, format.name()))
+}
+
+/// 创建采集使用的客户端，设置见 [`harvest_cli_builder`]
+pub fn harvest_cli(output_dir: impl AsRef<Path>) -> Result<Cli> {
+    harvest_cli_builder(output_dir).build()
+}
+
+/// 采集使用的客户端设置，可以在此基础上调整限速和重试策略
+///
+/// 所有请求（包括重试）平均每 1.5 秒一个，失败后按指数退避最多尝试 5 次；
+/// 原始响应记录在输出目录下的 `journal.ndjson`，可以离线重建输出。
+/// 默认不缓存响应，需要时加上 [`harvest_cache`]
+pub fn harvest_cli_builder(output_dir: impl AsRef<Path>) -> CliBuilder {
+    Cli::builder()
+        .rate_limit(RateLimit::per_minute(40).expect("默认请求速率大于 0"))
+        .journal(output_dir.as_ref().join("journal.ndjson"))
+        .retry_policy(
+            RetryPolicy::new(5)
+                .base_delay(Duration::from_secs(2))
+                .max_delay(Duration::from_secs(60)),
+        )
+}
+
+/// 采集使用的响应缓存：行政区划和详情缓存 30 天，搜索结果缓存 1 天，
+/// 重新采集时不再重复下载未过期的数据
+pub fn harvest_cache(cache_dir: impl Into<PathBuf>) -> CacheConfig {
+    const DAY: Duration = Duration::from_secs(24 * 3600);
+    CacheConfig::new(cache_dir)
+        .ttl("division", Some(DAY * 30))
+        .ttl("search", Some(DAY))
+        .ttl("details", Some(DAY * 30))
+}
+
+/// 搜索县级行政区下符合规则的地名，`year` 为空时搜索最新年版
+pub async fn search_county(
+    cli: &Cli,
+    county_code: &str,
+    rule: &PlaceTypeRule,
+    year: Option<i32>,
+) -> Result<Vec<Record>> {
+    let mut builder = SearchParamsBuilder::default();
+    builder
+        .st_name("")
+        .place_type_code(rule.code.as_str())
+        .code(county_code)
+        .search_type(SearchType::Fuzzy)
+        .page(1)
+        .size(1000);
+    if let Some(year) = year {
+        builder.year(year);
+    }
+    let params = builder.build().unwrap();
+
+    let results = cli
+        .search_complete(&params, &SplitStrategy::defaults())
+        .await?;
+    if results.is_truncated() {
+        eprintln!(
+            "警告: 县级行政区 {} 的{}搜索结果拆分查询后仍被截断，仅取回 {}/{} 条",
+            county_code,
+            rule.label(),
+            results.records.len(),
+            results.total
+        );
+    }
+    Ok(results
+        .records
+        .into_iter()
+        .filter(|r| rule.matches(r))
+        .collect())
+}
+
+/// 采集结果汇总
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HarvestSummary {
+    /// 县级行政区数
+    pub counties: usize,
+    /// 已完成的县级行政区数（包括之前运行完成的）
+    pub completed_counties: usize,
+    /// 本次搜索到的地名数
+    pub records: usize,
+    /// 重试后仍搜索失败的县级行政区划代码
+    pub failed_searches: Vec<String>,
+    /// 重试后仍获取失败的地名详情数
+    pub failed_details: usize,
+}
+
+/// 地名采集器
+pub struct Harvester {
+    cli: Cli,
+    region: Region,
+    rules: Vec<PlaceTypeRule>,
+    year: Option<i32>,
+    output_dir: PathBuf,
+    name: String,
+    formats: Vec<ExportFormat>,
+    retry_rounds: u32,
+    request_retries: u32,
+}
+
+impl Harvester {
+    /// 采集 `region` 内的地名，输出到 `output_dir`；默认采集最新年版的农村居民点，
+    /// 以全部格式输出，文件名为 `toponyms`
+    pub fn new(cli: Cli, region: Region, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cli,
+            region,
+            rules: vec![PlaceTypeRule::rural_settlements()],
+            year: None,
+            output_dir: output_dir.into(),
+            name: "toponyms".to_string(),
+            formats: HARVEST_FORMATS.to_vec(),
+            retry_rounds: DEFAULT_RETRY_ROUNDS,
+            request_retries: DEFAULT_REQUEST_RETRIES,
+        }
+    }
+
+    /// 设置要采集的地名类别，替换默认的农村居民点
+    pub fn rules(mut self, rules: impl IntoIterator<Item = PlaceTypeRule>) -> Self {
+        self.rules = rules.into_iter().collect();
+        self
+    }
+
+    /// 设置年版，默认为最新年版
+    pub fn year(mut self, year: Option<i32>) -> Self {
+        self.year = year;
+        self
+    }
+
+    /// 设置输出文件名（不含扩展名）
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// 设置输出格式，只支持 [`HARVEST_FORMATS`]；SQLite 数据库总会写入
+    pub fn formats(mut self, formats: impl IntoIterator<Item = ExportFormat>) -> Self {
+        self.formats = formats.into_iter().collect();
+        self
+    }
+
+    /// 设置全部县处理完后重试失败请求的最多轮数，以及单个请求最多重试的次数
+    pub fn retries(mut self, rounds: u32, per_request: u32) -> Self {
+        self.retry_rounds = rounds;
+        self.request_retries = per_request;
+        self
+    }
+
+    /// 使用的客户端
+    pub fn cli(&self) -> &Cli {
+        &self.cli
+    }
+
+    /// 运行采集，中断后再次运行会从中断处继续
+    pub async fn run(&self) -> Result<HarvestSummary> {
+        if self.rules.is_empty() {
+            return Err(crate::Error::Config("没有指定要采集的地名类别".to_string()));
+        }
+        if self.formats.is_empty() {
+            return Err(crate::Error::Config("没有指定输出格式".to_string()));
+        }
+        let output_dir = self.output_dir.as_path();
+        tokio::fs::create_dir_all(output_dir).await?;
+        let county_codes = self.region.county_codes(&self.cli, self.year).await?;
+        let total_counties = county_codes.len();
+
+        // 读取采集进度：有进度时跳过已完成的工作并追加到已有的输出文件，否则重新创建
+        let checkpoint_path = output_dir.join(CHECKPOINT_FILE);
+        let mut checkpoint = Checkpoint::open(&checkpoint_path, &self.rules)?;
+        let outputs_exist = self
+            .formats
+            .iter()
+            .all(|format| output_path(output_dir, &self.name, *format).exists());
+        if checkpoint.is_resuming() && !outputs_exist {
+            eprintln!("警告: 找到采集进度但输出文件不存在，重新开始采集");
+            checkpoint.remove()?;
+            checkpoint = Checkpoint::open(&checkpoint_path, &self.rules)?;
+        }
+        let mut sink = if checkpoint.is_resuming() {
+            eprintln!(
+                "从上次中断处继续：已完成 {} 个县级行政区，{} 个地名详情",
+                checkpoint.completed_counties.len(),
+                checkpoint.fetched_ids.len()
+            );
+            let mut sink = HarvestSink::append(output_dir, &self.name, &self.formats)?;
+            sink.mark_written(&checkpoint.fetched_ids);
+            sink
+        } else {
+            HarvestSink::create(output_dir, &self.name, &self.formats)?
+        };
+
+        // 如果存在之前的失败请求记录，则加载它们
+        let failed_requests_path = output_dir.join(FAILED_REQUESTS_FILE);
+        let mut failed_requests = Vec::new();
+        if failed_requests_path.exists() {
+            failed_requests = load_failed_requests(&failed_requests_path).await?;
+            if !failed_requests.is_empty() {
+                eprintln!("加载了 {} 个之前失败的请求", failed_requests.len());
+            }
+        }
+
+        let mut summary = HarvestSummary {
+            counties: total_counties,
+            ..Default::default()
+        };
+        for (i, county_code) in county_codes.iter().enumerate() {
+            if checkpoint.is_county_completed(county_code) {
+                eprintln!(
+                    "[{}/{}] 跳过已完成的县级行政区: {}",
+                    i + 1,
+                    total_counties,
+                    county_code
+                );
+                continue;
+            }
+            eprintln!(
+                "[{}/{}] 正在处理县级行政区: {}",
+                i + 1,
+                total_counties,
+                county_code
+            );
+
+            let mut search_failed = false;
+            for rule in &self.rules {
+                // 搜索失败时记入失败请求，稍后重试
+                let request = FailedRequest::search(county_code, rule);
+                let records = match search_county(&self.cli, county_code, rule, self.year).await {
+                    Ok(records) => records,
+                    Err(e) => {
+                        eprintln!("{} 失败: {}", request, e);
+                        sink.record_failure(&request, &e.to_string())?;
+                        if !failed_requests.iter().any(|r| r.key() == request.key()) {
+                            failed_requests.push(request);
+                        }
+                        search_failed = true;
+                        continue;
+                    }
+                };
+                // 之前搜索失败的这次已成功
+                failed_requests.retain(|r| r.key() != request.key());
+                sink.resolve_failure(&request)?;
+
+                eprintln!(
+                    "找到 {} 个{}，县级行政区: {}",
+                    records.len(),
+                    rule.label(),
+                    county_code
+                );
+                summary.records += records.len();
+                self.fetch_details(
+                    &mut sink,
+                    &mut checkpoint,
+                    county_code,
+                    records,
+                    &mut failed_requests,
+                )
+                .await?;
+            }
+
+            // 每处理完一个县，落盘输出文件并保存一次失败请求记录
+            flush(&mut sink, &mut checkpoint)?;
+            save_failed_requests(&failed_requests_path, &failed_requests).await?;
+            // 获取失败的详情已记入失败请求，稍后重试；搜索失败的县在重试成功后才算完成
+            if !search_failed {
+                checkpoint.complete_county(county_code)?;
+            }
+        }
+
+        let failed_searches = failed_requests
+            .iter()
+            .filter(|r| r.kind == RequestKind::Search)
+            .count();
+        eprintln!("处理完成");
+        eprintln!("总地名数: {}", summary.records);
+        eprintln!(
+            "完成的县级行政区数: {}/{}",
+            checkpoint.completed_counties.len(),
+            total_counties
+        );
+        eprintln!("失败的县级行政区搜索数: {}", failed_searches);
+        eprintln!(
+            "失败的详细信息请求数: {}",
+            failed_requests.len() - failed_searches
+        );
+        if let Some(stats) = self.cli.cache_stats() {
+            eprintln!(
+                "缓存命中: {}，未命中: {}，写入: {}",
+                stats.hits, stats.misses, stats.writes
+            );
+        }
+
+        // 如果有失败的请求，尝试重试
+        if !failed_requests.is_empty() {
+            eprintln!("开始重试失败的请求...");
+            self.retry_failed_requests(&mut failed_requests, &mut sink, &mut checkpoint)
+                .await?;
+        } else {
+            // 没有失败的请求时也要覆盖上次采集留下的记录，retry 命令依据此文件重试
+            save_failed_requests(
+                &output_dir.join(FINAL_FAILED_REQUESTS_FILE),
+                &failed_requests,
+            )
+            .await?;
+        }
+
+        // 补全输出文件结尾
+        sink.finish()?;
+
+        report_failures(&failed_requests, &mut summary);
+        summary.completed_counties = county_codes
+            .iter()
+            .filter(|code| checkpoint.is_county_completed(code))
+            .count();
+
+        // 所有县都处理完后删除采集进度，下次运行重新采集
+        if summary.completed_counties == total_counties {
+            checkpoint.remove()?;
+        } else {
+            eprintln!("仍有县级行政区未完成，再次运行将从中断处继续");
+        }
+
+        Ok(summary)
+    }
+
+    /// 重试上次采集最终仍失败的请求，成功的结果追加到已有的输出文件
+    ///
+    /// 读取输出目录下的 [`FINAL_FAILED_REQUESTS_FILE`]，每个请求的重试次数清零后按
+    /// [`retries`](Self::retries) 的设置重试
+    pub async fn retry(&self) -> Result<HarvestSummary> {
+        let output_dir = self.output_dir.as_path();
+        let final_failed_path = output_dir.join(FINAL_FAILED_REQUESTS_FILE);
+        if !final_failed_path.exists() {
+            return Err(crate::Error::Config(format!(
+                "没有找到 {}，请先完成一次采集",
+                final_failed_path.display()
+            )));
+        }
+        let mut failed_requests = load_failed_requests(&final_failed_path).await?;
+        let mut summary = HarvestSummary::default();
+        if failed_requests.is_empty() {
+            eprintln!("没有需要重试的请求");
+            return Ok(summary);
+        }
+        for request in &mut failed_requests {
+            request.retry_count = 0;
+        }
+        eprintln!("加载了 {} 个失败的请求", failed_requests.len());
+
+        // 采集中断时沿用其进度；采集已完成时重试产生的进度在结束后删除，以免下次采集误认为中断
+        let checkpoint_path = output_dir.join(CHECKPOINT_FILE);
+        let interrupted = checkpoint_path.exists();
+        let mut checkpoint = Checkpoint::open(&checkpoint_path, &self.rules)?;
+        let mut sink = HarvestSink::append(output_dir, &self.name, &self.formats)?;
+        sink.mark_written(&checkpoint.fetched_ids);
+        self.retry_failed_requests(&mut failed_requests, &mut sink, &mut checkpoint)
+            .await?;
+        sink.finish()?;
+        if !interrupted {
+            checkpoint.remove()?;
+        }
+
+        report_failures(&failed_requests, &mut summary);
+        Ok(summary)
+    }
+
+    /// 保存搜索结果并获取每个地名的详细信息，失败的请求加入 `failed_requests`
+    async fn fetch_details(
+        &self,
+        sink: &mut HarvestSink,
+        checkpoint: &mut Checkpoint,
+        county_code: &str,
+        records: Vec<Record>,
+        failed_requests: &mut Vec<FailedRequest>,
+    ) -> Result<()> {
+        sink.write_records(&records)?;
+
+        for record in records {
+            if checkpoint.is_fetched(&record.id) {
+                continue;
+            }
+            eprintln!("获取详细信息: {} ({})", record.standard_name, record.id);
+
+            // 获取详细信息，失败时客户端会按重试策略自动重试
+            let request = FailedRequest::details(&record, county_code);
+            match self.cli.details(&record.id).await {
+                Ok(details) => {
+                    // 上次中断前失败的这次已成功
+                    failed_requests.retain(|r| r.key() != request.key());
+                    if !sink.write(&details)? {
+                        eprintln!("跳过重复的地名: {} ({})", record.standard_name, record.id);
+                    }
+                    if sink.needs_flush() {
+                        flush(sink, checkpoint)?;
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "获取详细信息失败 {} ({}): {}",
+                        record.standard_name, record.id, e
+                    );
+                    sink.record_failure(&request, &e.to_string())?;
+                    if !failed_requests.iter().any(|r| r.key() == request.key()) {
+                        failed_requests.push(request);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 重试失败的请求
+    async fn retry_failed_requests(
+        &self,
+        failed_requests: &mut Vec<FailedRequest>,
+        sink: &mut HarvestSink,
+        checkpoint: &mut Checkpoint,
+    ) -> Result<()> {
+        let final_failed_path = self.output_dir.join(FINAL_FAILED_REQUESTS_FILE);
+        let mut round = 0;
+
+        while !failed_requests.is_empty() && round < self.retry_rounds {
+            round += 1;
+            eprintln!(
+                "第 {} 次重试，剩余 {} 个失败请求",
+                round,
+                failed_requests.len()
+            );
+
+            let mut successful_requests = HashSet::new();
+            // 重新搜索成功的县中获取详情失败的请求，下一轮重试
+            let mut new_requests = Vec::new();
+            // 重新搜索成功的县
+            let mut searched_counties = Vec::new();
+
+            for request in failed_requests.iter_mut() {
+                if request.retry_count >= self.request_retries {
+                    continue;
+                }
+                let done = match request.kind {
+                    RequestKind::Search => checkpoint.is_county_completed(&request.id),
+                    RequestKind::Details => checkpoint.is_fetched(&request.id),
+                };
+                if done {
+                    successful_requests.insert(request.key());
+                    continue;
+                }
+
+                eprintln!("重试请求: {}", request);
+
+                let result = match request.kind {
+                    RequestKind::Search => {
+                        self.retry_search(request, sink, checkpoint, &mut new_requests)
+                            .await
+                    }
+                    RequestKind::Details => match self.cli.details(&request.id).await {
+                        Ok(details) => {
+                            sink.write(&details)?;
+                            if sink.needs_flush() {
+                                flush(sink, checkpoint)?;
+                            }
+                            Ok(())
+                        }
+                        Err(e) => Err(e),
+                    },
+                };
+
+                match result {
+                    Ok(()) => {
+                        successful_requests.insert(request.key());
+                        if request.kind == RequestKind::Search {
+                            searched_counties.push(request.id.clone());
+                        }
+                        eprintln!("重试成功: {}", request);
+                    }
+                    Err(e) => {
+                        eprintln!("重试失败 {}: {}", request, e);
+                        request.retry_count += 1;
+                        sink.record_failure(request, &e.to_string())?;
+                    }
+                }
+            }
+
+            flush(sink, checkpoint)?;
+            // 移除成功的请求
+            failed_requests.retain(|r| !successful_requests.contains(&r.key()));
+            failed_requests.extend(new_requests);
+
+            // 一个县的所有搜索都成功后才算完成
+            for county_code in searched_counties {
+                let pending = failed_requests
+                    .iter()
+                    .any(|r| r.kind == RequestKind::Search && r.id == county_code);
+                if !pending && !checkpoint.is_county_completed(&county_code) {
+                    checkpoint.complete_county(&county_code)?;
+                }
+            }
+
+            // 每次重试批次后保存当前失败请求状态
+            save_failed_requests(&final_failed_path, failed_requests).await?;
+        }
+
+        // 保存最终的失败请求记录
+        if !failed_requests.is_empty() {
+            save_failed_requests(&final_failed_path, failed_requests).await?;
+            let failed_searches = failed_searches_after(failed_requests);
+            eprintln!(
+                "最终仍有 {} 个请求失败（县级行政区搜索 {} 个，详细信息 {} 个），已保存到 {}",
+                failed_requests.len(),
+                failed_searches,
+                failed_requests.len() - failed_searches,
+                final_failed_path.display()
+            );
+        } else {
+            eprintln!("所有失败请求已成功重试！");
+        }
+
+        Ok(())
+    }
+
+    /// 重新搜索县级行政区并获取搜索到的地名详情
+    async fn retry_search(
+        &self,
+        request: &FailedRequest,
+        sink: &mut HarvestSink,
+        checkpoint: &mut Checkpoint,
+        new_requests: &mut Vec<FailedRequest>,
+    ) -> Result<()> {
+        // 旧版本的记录没有类别代码，重新搜索全部类别
+        let rules: Vec<&PlaceTypeRule> = match &request.place_type_code {
+            Some(code) => self.rules.iter().filter(|r| &r.code == code).collect(),
+            None => self.rules.iter().collect(),
+        };
+        for rule in rules {
+            let records = search_county(&self.cli, &request.id, rule, self.year).await?;
+            eprintln!(
+                "找到 {} 个{}，县级行政区: {}",
+                records.len(),
+                rule.label(),
+                request.id
+            );
+            self.fetch_details(sink, checkpoint, &request.id, records, new_requests)
+                .await?;
+        }
+        sink.resolve_failure(request)
+    }
+}
+
+/// 把输出文件落盘，再在采集进度中记录落盘的地名，中断时不会把未落盘的地名记为已完成
+fn flush(sink: &mut HarvestSink, checkpoint: &mut Checkpoint) -> Result<()> {
+    for id in sink.flush()? {
+        checkpoint.record_fetched(&id)?;
+    }
+    Ok(())
+}
+
+/// 统计仍失败的请求，搜索仍然失败的县缺少数据，单独列出
+fn report_failures(failed_requests: &[FailedRequest], summary: &mut HarvestSummary) {
+    let mut missing_counties: Vec<String> = failed_requests
+        .iter()
+        .filter(|r| r.kind == RequestKind::Search)
+        .map(|r| r.id.clone())
+        .collect();
+    missing_counties.sort();
+    missing_counties.dedup();
+    if !missing_counties.is_empty() {
+        eprintln!(
+            "警告: 以下 {} 个县级行政区搜索失败，数据不完整: {}",
+            missing_counties.len(),
+            missing_counties.join(", ")
+        );
+    }
+    summary.failed_details = failed_requests.len() - failed_searches_after(failed_requests);
+    summary.failed_searches = missing_counties;
+}
+
+/// 失败请求中县级行政区搜索的个数
+fn failed_searches_after(requests: &[FailedRequest]) -> usize {
+    requests
+        .iter()
+        .filter(|r| r.kind == RequestKind::Search)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with, write_partial};
+    use crate::test_support::{sample_division, sample_record};
+
+    #[test]
+    fn test_place_type_rules() {
+        let record = |code: &str, place_type: &str, name: &str| Record {
+            place_type_code: Some(code.to_string()),
+            place_type: place_type.to_string(),
+            ..sample_record("abc", name)
+        };
+        let rural = PlaceTypeRule::rural_settlements();
+        assert!(rural.matches(&record("22200", "农村居民点", "唐庄")));
+        assert!(rural.matches(&record("22200", "", "唐庄村")));
+        assert!(!rural.matches(&record("22200", "", "唐庄")));
+        assert!(!rural.matches(&record("22100", "农村居民点", "唐庄村")));
+        assert!(!rural.matches(&record("22210", "农村居民点", "唐庄村")));
+
+        let any = PlaceTypeRule::new("24000").include_sub_types(true);
+        assert!(any.matches(&record("24110", "桥梁", "黄河大桥")));
+        assert!(!any.matches(&record("25110", "街路巷", "人民路")));
+        assert_eq!(any.label(), "24000");
+    }
+
+    #[test]
+    fn test_county_codes() {
+        assert!(is_county_code("410122000000"));
+        assert!(is_county_code("410122"));
+        assert!(is_county_code("419001000000"));
+        assert!(!is_county_code("410100000000"));
+        assert!(!is_county_code("410122100000"));
+
+        let mut codes = Vec::new();
+        collect_counties(&sample_division(), &mut codes);
+        assert_eq!(codes, ["410102", "410122", "410181", "410225", "419001"]);
+    }
+
+    #[tokio::test]
+    async fn test_region_counties() {
+        let cli = Cli::new();
+        let region = Region::counties(["410122", "410102"]);
+        assert_eq!(
+            region.county_codes(&cli, None).await.unwrap(),
+            ["410122", "410102"]
+        );
+        // 县级行政区不需要查询接口
+        let region = Region::division("410122000000");
+        assert_eq!(region.county_codes(&cli, None).await.unwrap(), ["410122"]);
+    }
+
+    #[test]
+    fn test_failed_request_kinds() {
+        // 旧版本的失败请求记录没有类型字段
+        let old: Vec<FailedRequest> = serde_json::from_str(
+            r#"[{"id":"abc","name":"唐庄村","county_code":"410122","retry_count":1}]"#,
+        )
+        .unwrap();
+        assert_eq!(old[0].kind, RequestKind::Details);
+        assert_eq!(old[0].to_string(), "唐庄村 (abc)");
+
+        let search = FailedRequest::search("410122", &PlaceTypeRule::rural_settlements());
+        assert_eq!(search.to_string(), "搜索县级行政区 410122（类别 22200）");
+        let json = serde_json::to_value(&search).unwrap();
+        assert_eq!(json["kind"], "search");
+        let failure = search.to_failure("超时");
+        assert_eq!(
+            (failure.kind, failure.target.as_str()),
+            (FailureKind::Search, "410122:22200")
+        );
+    }
+
+    #[test]
+    fn test_sink_unique_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink =
+            HarvestSink::create(dir.path(), "all_settlements", &HARVEST_FORMATS).unwrap();
+        assert!(sink.write(&sample_details()).unwrap());
+        assert!(!sink.write(&sample_details()).unwrap());
+        // 落盘时返回尚未落盘的地名，重复写入的只返回一次
+        assert_eq!(sink.flush().unwrap(), [sample_details().id]);
+        assert!(sink.flush().unwrap().is_empty());
+        drop(sink);
+
+        // 追加时读取已写入的 ID
+        let mut sink =
+            HarvestSink::append(dir.path(), "all_settlements", &HARVEST_FORMATS).unwrap();
+        assert!(!sink.write(&sample_details()).unwrap());
+        assert!(
+            sink.write(&sample_details_with(
+                "def",
+                r#"{"type":"Point","coordinates":[1,2]}"#
+            ))
+            .unwrap()
+        );
+        sink.finish().unwrap();
+
+        let csv = std::fs::read_to_string(dir.path().join("all_settlements.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_sink_needs_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink =
+            HarvestSink::create(dir.path(), "all_settlements", &[ExportFormat::Csv]).unwrap();
+        let point = r#"{"type":"Point","coordinates":[1,2]}"#;
+        for i in 0..FLUSH_INTERVAL {
+            assert!(!sink.needs_flush());
+            sink.write(&sample_details_with(&i.to_string(), point))
+                .unwrap();
+        }
+        assert!(sink.needs_flush());
+        assert_eq!(sink.flush().unwrap().len(), FLUSH_INTERVAL);
+        assert!(!sink.needs_flush());
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CHECKPOINT_FILE);
+        let rules = [PlaceTypeRule::rural_settlements()];
+        let mut checkpoint = Checkpoint::open(&path, &rules).unwrap();
+        assert!(!checkpoint.is_resuming());
+        checkpoint.record_fetched("abc").unwrap();
+        checkpoint.complete_county("410122").unwrap();
+        drop(checkpoint);
+        write_partial(&path, br#"{"type":"details","id":"de"#);
+
+        let mut checkpoint = Checkpoint::open(&path, &rules).unwrap();
+        assert!(checkpoint.is_resuming());
+        assert!(checkpoint.is_county_completed("410122"));
+        assert!(!checkpoint.is_county_completed("410102"));
+        assert!(checkpoint.is_fetched("abc"));
+        assert!(!checkpoint.is_fetched("def"));
+        checkpoint.record_fetched("def").unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(&path, &rules).unwrap();
+        assert!(checkpoint.is_fetched("def"));
+        checkpoint.remove().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_checkpoint_rules_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CHECKPOINT_FILE);
+        // 旧版本的进度没有 rules 记录
+        std::fs::write(
+            &path,
+            "{\"type\":\"county\",\"code\":\"410122\"}\n{\"type\":\"details\",\"id\":\"abc\"}\n",
+        )
+        .unwrap();
+        let rural = [PlaceTypeRule::rural_settlements()];
+        assert!(
+            Checkpoint::open(&path, &rural)
+                .unwrap()
+                .is_county_completed("410122")
+        );
+
+        // 增加类别后已完成的县需要重新搜索，已写入的详情不再重复获取
+        let rules = [
+            PlaceTypeRule::new("24000"),
+            PlaceTypeRule::rural_settlements(),
+        ];
+        let mut checkpoint = Checkpoint::open(&path, &rules).unwrap();
+        assert!(!checkpoint.is_county_completed("410122"));
+        assert!(checkpoint.is_fetched("abc"));
+        checkpoint.complete_county("410183").unwrap();
+        drop(checkpoint);
+
+        // 类别顺序不同视为相同
+        let reordered = [
+            PlaceTypeRule::rural_settlements(),
+            PlaceTypeRule::new("24000"),
+        ];
+        let checkpoint = Checkpoint::open(&path, &reordered).unwrap();
+        assert!(checkpoint.is_county_completed("410183"));
+        assert!(!checkpoint.is_county_completed("410122"));
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(&path, &rural).unwrap();
+        assert!(!checkpoint.is_county_completed("410183"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_search_recorded_and_retried() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // 第一次搜索失败，之后成功
+        Mock::given(path("/9095/stname/listPub"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/listPub"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"records":[],"total":0}"#))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cli = Cli::builder()
+            .base_url(format!("{}/9095/", server.uri()))
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        let harvester = |rounds| {
+            Harvester::new(
+                cli.clone(),
+                Region::Counties(vec!["410122".to_string()]),
+                dir.path(),
+            )
+            .formats([ExportFormat::Csv])
+            .retries(rounds, 3)
+        };
+        // 采集时不重试，失败的搜索留给 retry
+        let summary = harvester(0).run().await.unwrap();
+        assert_eq!(summary.failed_searches, ["410122"]);
+        assert_eq!(summary.completed_counties, 0);
+
+        let failed = load_failed_requests(&dir.path().join(FAILED_REQUESTS_FILE))
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].kind, RequestKind::Search);
+        assert_eq!(failed[0].id, "410122");
+        let storage = Storage::open(dir.path().join(STORAGE_FILE)).unwrap();
+        let failures = storage.failures().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, FailureKind::Search);
+        assert_eq!(failures[0].target, "410122:22200");
+
+        let summary = harvester(DEFAULT_RETRY_ROUNDS).retry().await.unwrap();
+        assert!(summary.failed_searches.is_empty());
+        assert!(storage.failures().unwrap().is_empty());
+        let failed = load_failed_requests(&dir.path().join(FINAL_FAILED_REQUESTS_FILE))
+            .await
+            .unwrap();
+        assert!(failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_details_not_duplicated() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // 前两次获取详情失败，之后成功
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(path("/9095/stname/detailsPub"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(serde_json::to_string(&sample_details()).unwrap()),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cli = Cli::builder()
+            .base_url(format!("{}/9095/", server.uri()))
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        let harvester = Harvester::new(cli, Region::Counties(vec![]), dir.path());
+        let mut sink = HarvestSink::create(dir.path(), "toponyms", &[ExportFormat::Csv]).unwrap();
+        let mut checkpoint = Checkpoint::open(dir.path().join(CHECKPOINT_FILE), &[]).unwrap();
+        let mut failed_requests = Vec::new();
+        let mut fetch = async |failed_requests: &mut Vec<FailedRequest>| {
+            harvester
+                .fetch_details(
+                    &mut sink,
+                    &mut checkpoint,
+                    "410122",
+                    vec![sample_record("abc", "唐庄村")],
+                    failed_requests,
+                )
+                .await
+                .unwrap();
+        };
+
+        // 重新采集中断的县时同一地名再次失败，只记录一次
+        fetch(&mut failed_requests).await;
+        fetch(&mut failed_requests).await;
+        assert_eq!(failed_requests.len(), 1);
+        // 再次获取成功后移除之前的失败记录
+        fetch(&mut failed_requests).await;
+        assert!(failed_requests.is_empty());
+    }
+}
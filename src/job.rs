@@ -0,0 +1,606 @@
+//! # 采集任务文件
+//!
+//! 用 TOML 文件描述一次采集：范围、地名类别、年版、输出格式和目录、请求速率以及重试策略，
+//! 调整采集内容不需要修改代码。[`HarvestJob::load`] 读取任务文件后先检查全部设置，
+//! 有问题时一次列出，不会采集到一半才出错。
+//!
+//! ```toml
+//! year = 2023
+//!
+//! [regions]
+//! codes = ["410100000000"]        # 省、市或县级行政区划代码
+//! names = ["开封市", "兰考县"]     # 河南省内的行政区名称
+//! # county_file = "county_codes.txt"
+//!
+//! [[place_types]]
+//! code = "22200"
+//! place_type = "农村居民点"
+//! name_keywords = ["村"]
+//!
+//! [output]
+//! dir = "zhengzhou"
+//! name = "toponyms"
+//! formats = ["csv", "geojson", "gpkg"]
+//!
+//! [politeness]
+//! requests_per_minute = 40
+//!
+//! [retry]
+//! max_attempts = 5
+//! base_delay_secs = 2
+//! max_delay_secs = 60
+//! rounds = 5
+//! per_request = 3
+//!
+//! [cache]
+//! enabled = true
+//! ```
+
+use crate::api::*;
+use crate::error::{Error, Result};
+use crate::export::ExportFormat;
+use crate::harvest::{
+    DEFAULT_REQUEST_RETRIES, DEFAULT_RETRY_ROUNDS, HARVEST_FORMATS, Harvester, PlaceTypeRule,
+    Region, harvest_cache, harvest_cli_builder,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 按名称查找行政区的范围：河南省
+const PROVINCE_CODE: &str = "410000000000";
+
+/// 采集任务
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HarvestJob {
+    /// 年版，默认为最新年版
+    #[serde(default)]
+    pub year: Option<i32>,
+    /// 采集范围
+    pub regions: RegionSpec,
+    /// 要采集的地名类别
+    pub place_types: Vec<PlaceTypeRule>,
+    /// 输出设置
+    pub output: OutputSpec,
+    /// 请求速率设置
+    #[serde(default)]
+    pub politeness: PolitenessSpec,
+    /// 重试设置
+    #[serde(default)]
+    pub retry: RetrySpec,
+    /// 响应缓存设置
+    #[serde(default)]
+    pub cache: CacheSpec,
+}
+
+/// 采集范围，三种方式可以同时使用，结果合并去重
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegionSpec {
+    /// 省、市或县级行政区划代码，可以只写前几位，例如 `4101`
+    #[serde(default)]
+    pub codes: Vec<String>,
+    /// 河南省内的市、县级行政区名称，例如 `开封市`
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// 每行一个县级行政区划代码的文件，相对路径相对于任务文件所在目录
+    #[serde(default)]
+    pub county_file: Option<PathBuf>,
+}
+
+/// 输出设置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputSpec {
+    /// 输出目录，请求日志、采集进度和 SQLite 数据库也保存在此目录；
+    /// 相对路径相对于任务文件所在目录
+    pub dir: PathBuf,
+    /// 输出文件名（不含扩展名），默认为 `toponyms`
+    #[serde(default = "default_output_name")]
+    pub name: String,
+    /// 输出格式，默认为 [`HARVEST_FORMATS`] 中的全部格式
+    #[serde(default = "default_formats")]
+    pub formats: Vec<ExportFormat>,
+}
+
+fn default_output_name() -> String {
+    "toponyms".to_string()
+}
+
+fn default_formats() -> Vec<ExportFormat> {
+    HARVEST_FORMATS.to_vec()
+}
+
+/// 请求速率设置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolitenessSpec {
+    /// 每分钟请求数（包括重试）
+    pub requests_per_minute: u32,
+    /// 允许的突发请求数
+    pub burst: Option<u32>,
+    /// 每天最多请求数，只在本次运行内计数
+    pub daily_quota: Option<u64>,
+    /// 单个请求的超时时间（秒）
+    pub timeout_secs: Option<u64>,
+    /// User-Agent
+    pub user_agent: Option<String>,
+}
+
+impl Default for PolitenessSpec {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 40,
+            burst: None,
+            daily_quota: None,
+            timeout_secs: None,
+            user_agent: None,
+        }
+    }
+}
+
+/// 重试设置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetrySpec {
+    /// 单个请求最多尝试的次数（包括第一次），见 [`RetryPolicy`]
+    pub max_attempts: u32,
+    /// 第一次重试前等待的时间（秒），之后按指数增长
+    pub base_delay_secs: u64,
+    /// 重试等待的最长时间（秒）
+    pub max_delay_secs: u64,
+    /// 全部县处理完后重试失败请求的最多轮数
+    pub rounds: u32,
+    /// 每个失败请求在重试阶段最多重试的次数
+    pub per_request: u32,
+}
+
+impl Default for RetrySpec {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_secs: 2,
+            max_delay_secs: 60,
+            rounds: DEFAULT_RETRY_ROUNDS,
+            per_request: DEFAULT_REQUEST_RETRIES,
+        }
+    }
+}
+
+/// 响应缓存设置，默认不缓存
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSpec {
+    /// 是否缓存响应，重新采集时不再重复下载未过期的数据
+    pub enabled: bool,
+    /// 缓存目录，默认为输出目录下的 `cache`；相对路径相对于任务文件所在目录
+    pub dir: Option<PathBuf>,
+}
+
+impl HarvestJob {
+    /// 读取并检查任务文件，其中的相对路径按任务文件所在目录解析
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let parse = || -> Result<Self> {
+            let mut job: Self = toml::from_str(&text).map_err(|e| Error::Config(e.to_string()))?;
+            if let Some(base) = path.parent() {
+                job.resolve_paths(base);
+            }
+            job.validate()?;
+            Ok(job)
+        };
+        parse().map_err(|e| Error::Config(format!("任务文件 {} 有误: {}", path.display(), e)))
+    }
+
+    /// 把相对路径解析为相对于 `base` 的路径
+    fn resolve_paths(&mut self, base: &Path) {
+        let resolve = |path: &mut PathBuf| {
+            // 空路径留给 validate 报错
+            if path.is_relative() && !path.as_os_str().is_empty() {
+                *path = base.join(&*path);
+            }
+        };
+        resolve(&mut self.output.dir);
+        if let Some(path) = &mut self.regions.county_file {
+            resolve(path);
+        }
+        if let Some(path) = &mut self.cache.dir {
+            resolve(path);
+        }
+    }
+
+    /// 解析并检查 TOML 格式的任务
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let job: Self = toml::from_str(text).map_err(|e| Error::Config(e.to_string()))?;
+        job.validate()?;
+        Ok(job)
+    }
+
+    /// 检查设置，列出全部问题
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let regions = &self.regions;
+        if regions.codes.is_empty() && regions.names.is_empty() && regions.county_file.is_none() {
+            problems.push("regions 至少需要 codes、names 或 county_file 之一".to_string());
+        }
+        for code in &regions.codes {
+            if code.len() < 2 || code.len() > 12 || !code.bytes().all(|b| b.is_ascii_digit()) {
+                problems.push(format!(
+                    "regions.codes 中的 \"{}\" 不是行政区划代码（2 到 12 位数字）",
+                    code
+                ));
+            }
+        }
+        if regions.names.iter().any(|name| name.trim().is_empty()) {
+            problems.push("regions.names 中有空的名称".to_string());
+        }
+        if let Some(path) = &regions.county_file
+            && !path.is_file()
+        {
+            problems.push(format!(
+                "regions.county_file 指定的文件 {} 不存在",
+                path.display()
+            ));
+        }
+
+        if self.place_types.is_empty() {
+            problems.push("place_types 至少需要一个地名类别".to_string());
+        }
+        for rule in &self.place_types {
+            if rule.code().len() != 5 || !rule.code().bytes().all(|b| b.is_ascii_digit()) {
+                problems.push(format!(
+                    "place_types 中的类别代码 \"{}\" 不是 5 位数字",
+                    rule.code()
+                ));
+            }
+        }
+
+        if let Some(year) = self.year
+            && !(1949..=2100).contains(&year)
+        {
+            problems.push(format!("year = {} 不是有效的年版", year));
+        }
+
+        let output = &self.output;
+        if output.dir.as_os_str().is_empty() {
+            problems.push("output.dir 不能为空".to_string());
+        }
+        if output.name.is_empty() || output.name.contains(['/', '\\']) {
+            problems.push(format!(
+                "output.name = \"{}\" 必须是不含路径分隔符的文件名",
+                output.name
+            ));
+        }
+        if output.formats.is_empty() {
+            problems.push("output.formats 至少需要一种输出格式".to_string());
+        }
+        for format in &output.formats {
+            if !HARVEST_FORMATS.contains(format) {
+                problems.push(format!(
+                    "output.formats 中的 \"{}\" 不能在采集时断点续写，只支持 csv、geojson 和 gpkg；\
+                     采集后可以用 export 命令从数据库导出",
+                    format
+                ));
+            }
+        }
+
+        if self.politeness.requests_per_minute == 0 {
+            problems.push("politeness.requests_per_minute 必须大于 0".to_string());
+        }
+        if self.politeness.timeout_secs == Some(0) {
+            problems.push("politeness.timeout_secs 必须大于 0".to_string());
+        }
+
+        let retry = &self.retry;
+        if retry.max_attempts == 0 {
+            problems.push("retry.max_attempts 必须大于 0".to_string());
+        }
+        if retry.base_delay_secs > retry.max_delay_secs {
+            problems.push(format!(
+                "retry.base_delay_secs = {} 不能大于 retry.max_delay_secs = {}",
+                retry.base_delay_secs, retry.max_delay_secs
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Config(format!(
+                "任务设置有 {} 处问题:\n  - {}",
+                problems.len(),
+                problems.join("\n  - ")
+            )))
+        }
+    }
+
+    /// 按请求速率、重试和缓存设置创建客户端，请求日志位于输出目录下
+    pub fn cli(&self) -> Result<Cli> {
+        let politeness = &self.politeness;
+        let mut rate_limit = RateLimit::per_minute(politeness.requests_per_minute)?;
+        if let Some(burst) = politeness.burst {
+            rate_limit = rate_limit.burst(burst);
+        }
+        let mut builder = harvest_cli_builder(&self.output.dir)
+            .rate_limit(rate_limit)
+            .retry_policy(
+                RetryPolicy::new(self.retry.max_attempts)
+                    .base_delay(Duration::from_secs(self.retry.base_delay_secs))
+                    .max_delay(Duration::from_secs(self.retry.max_delay_secs)),
+            );
+        if let Some(quota) = politeness.daily_quota {
+            builder = builder.daily_quota(quota);
+        }
+        if let Some(timeout) = politeness.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(user_agent) = &politeness.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if self.cache.enabled {
+            let dir = self
+                .cache
+                .dir
+                .clone()
+                .unwrap_or_else(|| self.output.dir.join("cache"));
+            builder = builder.cache(harvest_cache(dir));
+        }
+        builder.build()
+    }
+
+    /// 把采集范围展开为县级行政区划代码，名称查不到或不唯一时报错
+    pub async fn county_codes(&self, cli: &Cli) -> Result<Vec<String>> {
+        let mut regions: Vec<Region> = self
+            .regions
+            .codes
+            .iter()
+            .map(|code| Region::division(format!("{:0<12}", code)))
+            .collect();
+
+        if !self.regions.names.is_empty() {
+            let params = match self.year {
+                Some(year) => DivisionQueryParams::new(
+                    year,
+                    PROVINCE_CODE.to_string(),
+                    QueryLevel::GrandChild,
+                ),
+                None => DivisionQueryParams::latest(PROVINCE_CODE, QueryLevel::GrandChild),
+            };
+            let province = cli.execute(&params).await?.data;
+            let mut problems = Vec::new();
+            for name in &self.regions.names {
+                match find_by_name(&province, name.trim()).as_slice() {
+                    [division] => regions.push(Region::division(division.code.clone())),
+                    [] => problems.push(format!("找不到名为 \"{}\" 的行政区", name)),
+                    found => problems.push(format!(
+                        "名为 \"{}\" 的行政区不唯一: {}",
+                        name,
+                        found
+                            .iter()
+                            .map(|d| d.code.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                }
+            }
+            if !problems.is_empty() {
+                return Err(Error::Config(problems.join("\n")));
+            }
+        }
+
+        if let Some(path) = &self.regions.county_file {
+            regions.push(Region::from_county_file(path)?);
+        }
+
+        let mut codes = Vec::new();
+        for region in &regions {
+            for code in region.county_codes(cli, self.year).await? {
+                if !codes.contains(&code) {
+                    codes.push(code);
+                }
+            }
+        }
+        Ok(codes)
+    }
+
+    /// 创建采集器：先展开采集范围，确认所有行政区都能找到
+    pub async fn harvester(&self) -> Result<Harvester> {
+        let cli = self.cli()?;
+        let codes = self.county_codes(&cli).await?;
+        if codes.is_empty() {
+            return Err(Error::Config("采集范围内没有县级行政区".to_string()));
+        }
+        Ok(
+            Harvester::new(cli, Region::Counties(codes), &self.output.dir)
+                .rules(self.place_types.iter().cloned())
+                .year(self.year)
+                .name(&self.output.name)
+                .formats(self.output.formats.iter().copied())
+                .retries(self.retry.rounds, self.retry.per_request),
+        )
+    }
+}
+
+/// 在行政区划树中按名称查找
+fn find_by_name<'a>(division: &'a DivisonQueryResult, name: &str) -> Vec<&'a DivisonQueryResult> {
+    let mut found = Vec::new();
+    if division.name == name {
+        found.push(division);
+    }
+    for child in &division.children {
+        found.extend(find_by_name(child, name));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_division;
+
+    const JOB: &str = r#"
+year = 2023
+
+[regions]
+codes = ["410122000000", "4101"]
+
+[[place_types]]
+code = "22200"
+place_type = "农村居民点"
+name_keywords = ["村"]
+
+[[place_types]]
+code = "24000"
+include_sub_types = true
+
+[output]
+dir = '{dir}'
+formats = ["csv", "gpkg"]
+
+[politeness]
+requests_per_minute = 20
+"#;
+
+    /// 输出目录放在临时目录中，测试不会写入工作目录
+    fn job_text(dir: &Path) -> String {
+        JOB.replace("{dir}", &dir.display().to_string())
+    }
+
+    #[test]
+    fn test_parse_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let job = HarvestJob::from_toml(&job_text(dir.path())).unwrap();
+        assert_eq!(job.year, Some(2023));
+        assert_eq!(job.place_types[0], PlaceTypeRule::rural_settlements());
+        assert_eq!(
+            job.place_types[1],
+            PlaceTypeRule::new("24000").include_sub_types(true)
+        );
+        assert_eq!(job.output.name, "toponyms");
+        assert_eq!(
+            job.output.formats,
+            [ExportFormat::Csv, ExportFormat::GeoPackage]
+        );
+        assert_eq!(job.politeness.requests_per_minute, 20);
+        assert_eq!(job.retry, RetrySpec::default());
+        assert_eq!(job.output.dir, dir.path());
+        assert_eq!(job.cache, CacheSpec::default());
+        assert!(job.cli().unwrap().cache().is_none());
+
+        let job = HarvestJob::from_toml(&format!(
+            "{}\n[cache]\nenabled = true\n",
+            job_text(dir.path())
+        ))
+        .unwrap();
+        assert!(job.cache.enabled);
+        assert!(job.cli().unwrap().cache().is_some());
+    }
+
+    #[test]
+    fn test_validation_lists_all_problems() {
+        let err = HarvestJob::from_toml(
+            r#"
+year = 1800
+[regions]
+codes = ["41x"]
+[[place_types]]
+code = "222"
+[output]
+dir = "out"
+formats = []
+[retry]
+base_delay_secs = 90
+"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("5 处问题"), "{}", err);
+        assert!(err.contains("\"41x\""));
+        assert!(err.contains("\"222\""));
+        assert!(err.contains("year = 1800"));
+        assert!(err.contains("output.formats"));
+        assert!(err.contains("retry.base_delay_secs = 90"));
+
+        // 拼错的字段和无法识别的格式在解析时报错
+        let dir = tempfile::tempdir().unwrap();
+        let job = job_text(dir.path());
+        let err = HarvestJob::from_toml(&job.replace("requests_per_minute", "requests_per_min"))
+            .unwrap_err();
+        assert!(err.to_string().contains("requests_per_min"));
+        let err = HarvestJob::from_toml(&job.replace("\"gpkg\"", "\"xlsx\"")).unwrap_err();
+        assert!(err.to_string().contains("xlsx"));
+
+        // 导出格式中不能断点续写的在检查时报错
+        for format in ["shp", "parquet"] {
+            let err = HarvestJob::from_toml(&job.replace("\"gpkg\"", &format!("\"{}\"", format)))
+                .unwrap_err()
+                .to_string();
+            assert!(
+                err.contains(&format!(
+                    "output.formats 中的 \"{}\" 不能在采集时断点续写",
+                    format
+                )),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_resolves_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("county_codes.txt"), "410122\n").unwrap();
+        let path = dir.path().join("job.toml");
+        std::fs::write(
+            &path,
+            r#"
+[regions]
+county_file = "county_codes.txt"
+[[place_types]]
+code = "22200"
+[output]
+dir = "out"
+[cache]
+dir = "cache"
+"#,
+        )
+        .unwrap();
+
+        // 在其他工作目录下读取时，相对路径仍相对于任务文件
+        let job = HarvestJob::load(&path).unwrap();
+        assert_eq!(
+            job.regions.county_file.as_deref(),
+            Some(dir.path().join("county_codes.txt").as_path())
+        );
+        assert_eq!(job.output.dir, dir.path().join("out"));
+        assert_eq!(job.cache.dir, Some(dir.path().join("cache")));
+    }
+
+    #[tokio::test]
+    async fn test_county_codes_without_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let county_file = dir.path().join("county_codes.txt");
+        std::fs::write(&county_file, "410122\n410183\n").unwrap();
+        let job = HarvestJob {
+            regions: RegionSpec {
+                codes: vec!["410122".to_string()],
+                county_file: Some(county_file),
+                ..Default::default()
+            },
+            ..HarvestJob::from_toml(&job_text(dir.path())).unwrap()
+        };
+        let cli = Cli::new();
+        assert_eq!(job.county_codes(&cli).await.unwrap(), ["410122", "410183"]);
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let province = sample_division();
+        let found = find_by_name(&province, "兰考县");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, "410225000000");
+        assert!(find_by_name(&province, "洛阳市").is_empty());
+    }
+}
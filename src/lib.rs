@@ -1,5 +1,29 @@
 pub mod api;
 
+pub mod error;
+pub use error::{Error, Result};
+
+pub mod crs;
+
+mod time;
+
+pub mod export;
+
 pub mod filter;
 
+pub mod storage;
+
+pub mod dedup;
+
+pub mod harvest;
+
+pub mod job;
+
+pub mod output;
+
+pub mod command;
+
 pub mod details;
+
+#[cfg(test)]
+pub(crate) mod test_support;
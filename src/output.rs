@@ -0,0 +1,365 @@
+//! # 命令行输出
+//!
+//! 按 [`PrintFormat`] 打印查询结果：对齐的表格、JSON 数组、每行一个对象的 NDJSON 或 CSV。
+//! JSON 和 NDJSON 保留接口返回的完整结构，表格和 CSV 使用 [`Row`] 提供的扁平列；
+//! 表格中过长的值会被截断，需要完整内容时使用其他格式。
+
+use crate::api::{DetailsQueryResponse, DivisonQueryResult, Record};
+use crate::error::Result;
+use crate::export::{CSV_HEADER, FlatRecord};
+use serde::Serialize;
+use std::io::Write;
+
+/// 表格中单元格的最大显示宽度
+const MAX_CELL_WIDTH: usize = 40;
+
+/// 打印格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PrintFormat {
+    /// 对齐的表格
+    #[default]
+    Table,
+    /// JSON 数组
+    Json,
+    /// 每行一个 JSON 对象
+    Ndjson,
+    /// CSV，带表头
+    Csv,
+}
+
+/// 可以按行打印的数据
+pub trait Row: Serialize {
+    /// 列名
+    fn columns() -> &'static [&'static str];
+
+    /// 各列的值，与 [`columns`](Self::columns) 一一对应
+    fn cells(&self) -> Vec<String>;
+
+    /// 表格中显示的列，默认为全部列
+    fn table_columns() -> &'static [&'static str] {
+        Self::columns()
+    }
+}
+
+/// 按格式打印
+pub fn print_rows<T: Row, W: Write>(mut writer: W, rows: &[T], format: PrintFormat) -> Result<()> {
+    match format {
+        PrintFormat::Table => write_table(&mut writer, rows)?,
+        PrintFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, rows).map_err(std::io::Error::from)?;
+            writeln!(writer)?;
+        }
+        PrintFormat::Ndjson => {
+            for row in rows {
+                serde_json::to_writer(&mut writer, row).map_err(std::io::Error::from)?;
+                writeln!(writer)?;
+            }
+        }
+        PrintFormat::Csv => {
+            let mut csv = ::csv::Writer::from_writer(&mut writer);
+            csv.write_record(T::columns())?;
+            for row in rows {
+                csv.write_record(row.cells())?;
+            }
+            csv.flush()?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_table<T: Row, W: Write>(writer: &mut W, rows: &[T]) -> Result<()> {
+    let columns = T::columns();
+    let indices: Vec<usize> = T::table_columns()
+        .iter()
+        .filter_map(|name| columns.iter().position(|c| c == name))
+        .collect();
+    let header: Vec<String> = indices.iter().map(|&i| columns[i].to_string()).collect();
+    let body: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            let cells = row.cells();
+            indices
+                .iter()
+                .map(|&i| truncate(cells[i].replace(['\n', '\r'], " ").as_str()))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|h| display_width(h)).collect();
+    for cells in &body {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+
+    let line = |cells: &[String]| {
+        let mut line = String::new();
+        for (i, (cell, width)) in cells.iter().zip(&widths).enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.push_str(&" ".repeat(width - display_width(cell)));
+        }
+        line.trim_end().to_string()
+    };
+    writeln!(writer, "{}", line(&header))?;
+    let separators: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    writeln!(writer, "{}", line(&separators))?;
+    for cells in &body {
+        writeln!(writer, "{}", line(cells))?;
+    }
+    Ok(())
+}
+
+/// 截断到 [`MAX_CELL_WIDTH`]，末尾加省略号
+fn truncate(text: &str) -> String {
+    if display_width(text) <= MAX_CELL_WIDTH {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        width += char_width(c);
+        if width > MAX_CELL_WIDTH - 1 {
+            break;
+        }
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
+/// 终端中的显示宽度，中日韩文字和全角符号占两列
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn option(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn coordinate(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl Row for Record {
+    fn columns() -> &'static [&'static str] {
+        &[
+            "id",
+            "standard_name",
+            "roman_alphabet_spelling",
+            "place_type",
+            "place_type_code",
+            "place_code",
+            "province_name",
+            "city_name",
+            "area_name",
+            "area",
+            "lon",
+            "lat",
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let center = self.gdm.as_ref().and_then(|gdm| gdm.centroid());
+        vec![
+            self.id.clone(),
+            self.standard_name.clone(),
+            self.roman_alphabet_spelling.clone(),
+            self.place_type.clone(),
+            option(&self.place_type_code),
+            self.place_code.clone(),
+            option(&self.province_name),
+            option(&self.city_name),
+            option(&self.area_name),
+            option(&self.area),
+            coordinate(center.map(|(lon, _)| lon)),
+            coordinate(center.map(|(_, lat)| lat)),
+        ]
+    }
+
+    fn table_columns() -> &'static [&'static str] {
+        &[
+            "standard_name",
+            "place_type",
+            "place_type_code",
+            "city_name",
+            "area_name",
+            "id",
+        ]
+    }
+}
+
+/// 与导出的 CSV 文件列相同
+impl Row for DetailsQueryResponse {
+    fn columns() -> &'static [&'static str] {
+        &CSV_HEADER
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let flat = FlatRecord::from(self);
+        vec![
+            flat.id,
+            flat.standard_name,
+            flat.roman_alphabet_spelling,
+            flat.place_type,
+            flat.place_type_code,
+            flat.place_code,
+            flat.province,
+            flat.province_name,
+            option(&flat.city),
+            option(&flat.city_name),
+            option(&flat.area),
+            option(&flat.area_name),
+            option(&flat.old_name),
+            flat.ethnic_minorities_writing,
+            flat.place_meaning,
+            flat.place_origin,
+            option(&flat.government_history),
+            coordinate(flat.lon),
+            coordinate(flat.lat),
+            flat.geometry_type,
+            flat.wkt,
+        ]
+    }
+
+    fn table_columns() -> &'static [&'static str] {
+        &[
+            "standard_name",
+            "place_type",
+            "area_name",
+            "lon",
+            "lat",
+            "place_meaning",
+            "id",
+        ]
+    }
+}
+
+/// 行政区划树中的一个节点
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DivisionRow {
+    /// 行政区划代码
+    pub code: String,
+    /// 标准名称
+    pub name: String,
+    /// 行政区划级别
+    pub level: i32,
+    /// 行政区划单位
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// 上级区划代码
+    pub parent: Option<String>,
+}
+
+impl DivisionRow {
+    /// 按先序展开行政区划树
+    pub fn flatten(division: &DivisonQueryResult) -> Vec<Self> {
+        let mut rows = Vec::new();
+        Self::collect(division, None, &mut rows);
+        rows
+    }
+
+    fn collect(division: &DivisonQueryResult, parent: Option<&str>, rows: &mut Vec<Self>) {
+        rows.push(Self::new(division, parent));
+        for child in &division.children {
+            Self::collect(child, Some(&division.code), rows);
+        }
+    }
+
+    /// 单个节点，不含下级区划
+    pub fn new(division: &DivisonQueryResult, parent: Option<&str>) -> Self {
+        Self {
+            code: division.code.clone(),
+            name: division.name.clone(),
+            level: division.level,
+            r#type: division.r#type.clone(),
+            parent: parent.map(str::to_string),
+        }
+    }
+}
+
+impl Row for DivisionRow {
+    fn columns() -> &'static [&'static str] {
+        &["code", "name", "level", "type", "parent"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.code.clone(),
+            self.name.clone(),
+            self.level.to_string(),
+            self.r#type.clone(),
+            option(&self.parent),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::sample_details;
+    use crate::test_support::sample_division;
+
+    fn print(rows: &[DivisionRow], format: PrintFormat) -> String {
+        let mut out = Vec::new();
+        print_rows(&mut out, rows, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_print_formats() {
+        // 开封市及其下的兰考县
+        let division = &sample_division().children[1];
+        let rows = DivisionRow::flatten(division);
+
+        assert_eq!(
+            print(&rows, PrintFormat::Table),
+            "code          name    level  type    parent\n\
+             ------------  ------  -----  ------  ------------\n\
+             410200000000  开封市  2      地级市\n\
+             410225000000  兰考县  3      县      410200000000\n"
+        );
+        assert_eq!(
+            print(&rows, PrintFormat::Csv),
+            "code,name,level,type,parent\n410200000000,开封市,2,地级市,\n410225000000,兰考县,3,县,410200000000\n"
+        );
+        let ndjson = print(&rows, PrintFormat::Ndjson);
+        assert_eq!(ndjson.lines().count(), 2);
+        assert!(ndjson.starts_with(
+            r#"{"code":"410200000000","name":"开封市","level":2,"type":"地级市","parent":null}"#
+        ));
+        let json: serde_json::Value =
+            serde_json::from_str(&print(&rows, PrintFormat::Json)).unwrap();
+        assert_eq!(json[1]["parent"], "410200000000");
+    }
+
+    #[test]
+    fn test_details_table() {
+        let mut details = sample_details();
+        details.place_meaning = "很长的含义".repeat(10);
+        let mut out = Vec::new();
+        print_rows(&mut out, &[details], PrintFormat::Table).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let row = table.lines().nth(2).unwrap();
+        assert!(row.starts_with("唐庄村"));
+        assert!(row.contains("…"));
+        assert!(row.ends_with("abc"));
+    }
+}
@@ -0,0 +1,728 @@
+//! # SQLite 存储
+//!
+//! 把搜索结果、地名详情、采集批次和失败请求写入本地 SQLite 数据库，
+//! 同一地名按 `id` 更新而不是重复插入。其他工具可以通过 [`Storage`] 的读取接口
+//! 查询本地数据，而不必访问线上服务。
+//!
+//! 数据库包含四张表：
+//! - `harvest_runs`：采集批次，记录开始、结束时间和状态
+//! - `records`：搜索结果（地名摘要）
+//! - `details`：地名详情，`gdm` 以 GeoJSON 文本保存，另有中心点 `lon`、`lat` 列
+//! - `failures`：尚未成功的请求，按 `(kind, target)` 唯一，成功后删除
+
+use crate::api::{DetailsQueryResponse, Geometry, Record};
+use crate::error::{Error, Result};
+use rusqlite::{Connection, OptionalExtension, Row, ToSql, params};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 采集批次 ID
+pub type RunId = i64;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS harvest_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    description TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running',
+    started_at INTEGER NOT NULL,
+    finished_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS records (
+    id TEXT PRIMARY KEY,
+    place_code TEXT NOT NULL,
+    standard_name TEXT NOT NULL,
+    roman_alphabet_spelling TEXT NOT NULL,
+    ethnic_minorities_writing TEXT,
+    place_type TEXT NOT NULL,
+    place_type_code TEXT,
+    province TEXT,
+    province_name TEXT,
+    city TEXT,
+    city_name TEXT,
+    area TEXT,
+    area_name TEXT,
+    gdm TEXT,
+    run_id INTEGER REFERENCES harvest_runs(id),
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS details (
+    id TEXT PRIMARY KEY,
+    standard_name TEXT NOT NULL,
+    roman_alphabet_spelling TEXT NOT NULL,
+    place_type TEXT NOT NULL,
+    place_type_code TEXT NOT NULL,
+    place_code TEXT NOT NULL,
+    province TEXT NOT NULL,
+    province_name TEXT NOT NULL,
+    city TEXT,
+    city_name TEXT,
+    area TEXT,
+    area_name TEXT,
+    old_name TEXT,
+    ethnic_minorities_writing TEXT NOT NULL,
+    place_meaning TEXT NOT NULL,
+    place_origin TEXT NOT NULL,
+    government_history TEXT,
+    gdm TEXT NOT NULL,
+    lon REAL,
+    lat REAL,
+    run_id INTEGER REFERENCES harvest_runs(id),
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS failures (
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    name TEXT,
+    county_code TEXT,
+    error TEXT NOT NULL,
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    run_id INTEGER REFERENCES harvest_runs(id),
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, target)
+);
+CREATE INDEX IF NOT EXISTS idx_records_name ON records(standard_name);
+CREATE INDEX IF NOT EXISTS idx_records_place_type ON records(place_type_code);
+CREATE INDEX IF NOT EXISTS idx_records_area ON records(area);
+CREATE INDEX IF NOT EXISTS idx_details_name ON details(standard_name);
+CREATE INDEX IF NOT EXISTS idx_details_place_type ON details(place_type_code);
+CREATE INDEX IF NOT EXISTS idx_details_area ON details(area);
+"#;
+
+/// `records` 表的数据列（不含批次和更新时间）
+const RECORD_COLUMNS: [&str; 14] = [
+    "id",
+    "place_code",
+    "standard_name",
+    "roman_alphabet_spelling",
+    "ethnic_minorities_writing",
+    "place_type",
+    "place_type_code",
+    "province",
+    "province_name",
+    "city",
+    "city_name",
+    "area",
+    "area_name",
+    "gdm",
+];
+
+/// `details` 表的数据列（不含批次和更新时间）
+const DETAILS_COLUMNS: [&str; 20] = [
+    "id",
+    "standard_name",
+    "roman_alphabet_spelling",
+    "place_type",
+    "place_type_code",
+    "place_code",
+    "province",
+    "province_name",
+    "city",
+    "city_name",
+    "area",
+    "area_name",
+    "old_name",
+    "ethnic_minorities_writing",
+    "place_meaning",
+    "place_origin",
+    "government_history",
+    "gdm",
+    "lon",
+    "lat",
+];
+
+/// 失败请求的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    /// 县级行政区搜索失败，目标为行政区划代码
+    Search,
+    /// 地名详情获取失败，目标为地名 ID
+    Details,
+}
+
+impl FailureKind {
+    /// 数据库中保存的名称
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Details => "details",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "search" => Some(Self::Search),
+            "details" => Some(Self::Details),
+            _ => None,
+        }
+    }
+}
+
+/// 失败请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// 请求类型
+    pub kind: FailureKind,
+    /// 请求目标：地名 ID 或行政区划代码
+    pub target: String,
+    /// 地名或行政区名称
+    pub name: Option<String>,
+    /// 所在县级行政区划代码
+    pub county_code: Option<String>,
+    /// 最近一次的错误信息
+    pub error: String,
+    /// 已重试次数
+    pub retry_count: u32,
+}
+
+/// 采集批次
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarvestRun {
+    /// 批次 ID
+    pub id: RunId,
+    /// 描述
+    pub description: String,
+    /// 状态：`running`、`finished` 或调用方指定的其他值
+    pub status: String,
+    /// 开始时间（毫秒时间戳）
+    pub started_at: i64,
+    /// 结束时间（毫秒时间戳）
+    pub finished_at: Option<i64>,
+}
+
+/// 地名查询条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default)]
+pub struct ToponymQuery {
+    name: Option<String>,
+    place_type_code: Option<String>,
+    region: Option<String>,
+    limit: Option<usize>,
+}
+
+impl ToponymQuery {
+    /// 创建不带条件的查询
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 标准名称包含指定文字
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// 地名类别代码
+    pub fn place_type_code(mut self, code: impl Into<String>) -> Self {
+        self.place_type_code = Some(code.into());
+        self
+    }
+
+    /// 所在行政区划，可以是省、市或区县代码，按简化代码前缀匹配区县代码
+    pub fn region(mut self, code: impl AsRef<str>) -> Self {
+        self.region = Some(crate::api::simplify_code(code.as_ref()));
+        self
+    }
+
+    /// 最多返回的条数
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// 生成 `WHERE ... ORDER BY ... LIMIT ...` 子句及参数
+    fn to_sql(&self) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(name) = &self.name {
+            conditions.push("standard_name LIKE ? ESCAPE '\\'");
+            let escaped = name
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            values.push(Box::new(format!("%{}%", escaped)));
+        }
+        if let Some(code) = &self.place_type_code {
+            conditions.push("place_type_code = ?");
+            values.push(Box::new(code.clone()));
+        }
+        if let Some(region) = &self.region {
+            conditions.push("substr(area, 1, length(?)) = ?");
+            values.push(Box::new(region.clone()));
+            values.push(Box::new(region.clone()));
+        }
+        let mut sql = String::new();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY area, id");
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        (sql, values)
+    }
+}
+
+/// SQLite 存储
+pub struct Storage {
+    conn: Connection,
+}
+
+impl Storage {
+    /// 打开（必要时创建）数据库文件
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        // 写入时允许其他进程同时读取
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        Self::init(conn)
+    }
+
+    /// 创建内存数据库
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// 开始新的采集批次
+    pub fn begin_run(&self, description: &str) -> Result<RunId> {
+        self.conn.execute(
+            "INSERT INTO harvest_runs (description, started_at) VALUES (?1, ?2)",
+            params![description, now_millis()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 结束采集批次并记录状态
+    pub fn finish_run(&self, run: RunId, status: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE harvest_runs SET status = ?2, finished_at = ?3 WHERE id = ?1",
+            params![run, status, now_millis()],
+        )?;
+        Ok(())
+    }
+
+    /// 写入（或更新）搜索结果
+    pub fn upsert_records(&mut self, run: Option<RunId>, records: &[Record]) -> Result<()> {
+        let sql = upsert_sql("records", &RECORD_COLUMNS);
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(&sql)?;
+            let now = now_millis();
+            for record in records {
+                let gdm = record.gdm.as_ref().map(geometry_json).transpose()?;
+                stmt.execute(params![
+                    record.id,
+                    record.place_code,
+                    record.standard_name,
+                    record.roman_alphabet_spelling,
+                    record.ethnic_minorities_writing,
+                    record.place_type,
+                    record.place_type_code,
+                    record.province,
+                    record.province_name,
+                    record.city,
+                    record.city_name,
+                    record.area,
+                    record.area_name,
+                    gdm,
+                    run,
+                    now,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 在一个事务中写入（或更新）一批地名详情，同时清除对应的失败记录
+    pub fn upsert_details(
+        &mut self,
+        run: Option<RunId>,
+        details: &[DetailsQueryResponse],
+    ) -> Result<()> {
+        let sql = upsert_sql("details", &DETAILS_COLUMNS);
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(&sql)?;
+            let mut resolve =
+                tx.prepare_cached("DELETE FROM failures WHERE kind = ?1 AND target = ?2")?;
+            let now = now_millis();
+            for details in details {
+                let center = details.gdm.centroid();
+                stmt.execute(params![
+                    details.id,
+                    details.standard_name,
+                    details.roman_alphabet_spelling,
+                    details.place_type,
+                    details.place_type_code,
+                    details.place_code,
+                    details.province,
+                    details.province_name,
+                    details.city,
+                    details.city_name,
+                    details.area,
+                    details.area_name,
+                    details.old_name,
+                    details.ethnic_minorities_writing,
+                    details.place_meaning,
+                    details.place_origin,
+                    details.government_history,
+                    geometry_json(&details.gdm)?,
+                    center.map(|(lon, _)| lon),
+                    center.map(|(_, lat)| lat),
+                    run,
+                    now,
+                ])?;
+                resolve.execute(params![FailureKind::Details.as_str(), details.id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 记录（或更新）失败请求
+    pub fn record_failure(&self, run: Option<RunId>, failure: &Failure) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO failures (kind, target, name, county_code, error, retry_count, run_id, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(kind, target) DO UPDATE SET
+                name = excluded.name, county_code = excluded.county_code, error = excluded.error,
+                retry_count = excluded.retry_count, run_id = excluded.run_id, updated_at = excluded.updated_at",
+            params![
+                failure.kind.as_str(),
+                failure.target,
+                failure.name,
+                failure.county_code,
+                failure.error,
+                failure.retry_count,
+                run,
+                now_millis(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 请求已成功，删除失败记录
+    pub fn resolve_failure(&self, kind: FailureKind, target: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM failures WHERE kind = ?1 AND target = ?2",
+            params![kind.as_str(), target],
+        )?;
+        Ok(())
+    }
+
+    /// 按 ID 读取地名详情
+    pub fn details(&self, id: &str) -> Result<Option<DetailsQueryResponse>> {
+        let sql = format!(
+            "SELECT {} FROM details WHERE id = ?1",
+            DETAILS_COLUMNS.join(", ")
+        );
+        self.conn
+            .query_row(&sql, [id], details_from_row)
+            .optional()?
+            .transpose()
+    }
+
+    /// 查询地名详情
+    pub fn query_details(&self, query: &ToponymQuery) -> Result<Vec<DetailsQueryResponse>> {
+        let (clause, values) = query.to_sql();
+        let sql = format!(
+            "SELECT {} FROM details{}",
+            DETAILS_COLUMNS.join(", "),
+            clause
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values.iter()), details_from_row)?;
+        rows.map(|row| row?).collect()
+    }
+
+    /// 查询搜索结果
+    pub fn query_records(&self, query: &ToponymQuery) -> Result<Vec<Record>> {
+        let (clause, values) = query.to_sql();
+        let sql = format!(
+            "SELECT {} FROM records{}",
+            RECORD_COLUMNS.join(", "),
+            clause
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values.iter()), record_from_row)?;
+        rows.map(|row| row?).collect()
+    }
+
+    /// 已保存的地名详情数
+    pub fn count_details(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT count(*) FROM details", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// 有搜索结果但还没有详情的地名 ID
+    pub fn missing_details(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.id FROM records r LEFT JOIN details d ON d.id = r.id
+             WHERE d.id IS NULL ORDER BY r.area, r.id",
+        )?;
+        let ids = stmt.query_map([], |row| row.get(0))?;
+        Ok(ids.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 尚未成功的请求
+    pub fn failures(&self) -> Result<Vec<Failure>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, target, name, county_code, error, retry_count FROM failures
+             ORDER BY kind, target",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let kind: String = row.get(0)?;
+            Ok((
+                kind,
+                Failure {
+                    kind: FailureKind::Details,
+                    target: row.get(1)?,
+                    name: row.get(2)?,
+                    county_code: row.get(3)?,
+                    error: row.get(4)?,
+                    retry_count: row.get(5)?,
+                },
+            ))
+        })?;
+        rows.map(|row| {
+            let (kind, mut failure) = row?;
+            failure.kind = FailureKind::parse(&kind)
+                .ok_or_else(|| Error::Config(format!("未知的失败请求类型: {}", kind)))?;
+            Ok(failure)
+        })
+        .collect()
+    }
+
+    /// 全部采集批次，按开始时间排序
+    pub fn runs(&self) -> Result<Vec<HarvestRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, description, status, started_at, finished_at FROM harvest_runs ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(HarvestRun {
+                id: row.get(0)?,
+                description: row.get(1)?,
+                status: row.get(2)?,
+                started_at: row.get(3)?,
+                finished_at: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// 生成按 `id` 更新的插入语句，末尾追加 `run_id` 和 `updated_at` 两列
+fn upsert_sql(table: &str, columns: &[&str]) -> String {
+    let all: Vec<&str> = columns
+        .iter()
+        .copied()
+        .chain(["run_id", "updated_at"])
+        .collect();
+    let placeholders: Vec<String> = (1..=all.len()).map(|i| format!("?{}", i)).collect();
+    let updates: Vec<String> = all[1..]
+        .iter()
+        .map(|column| format!("{0} = excluded.{0}", column))
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT(id) DO UPDATE SET {}",
+        table,
+        all.join(", "),
+        placeholders.join(", "),
+        updates.join(", ")
+    )
+}
+
+/// 序列化为 GeoJSON 文本
+fn geometry_json(geometry: &Geometry) -> Result<String> {
+    Ok(serde_json::to_string(geometry).map_err(std::io::Error::from)?)
+}
+
+/// 解析 GeoJSON 文本列
+fn geometry_column(row: &Row<'_>, index: usize) -> rusqlite::Result<Option<Result<Geometry>>> {
+    let text: Option<String> = row.get(index)?;
+    Ok(text.map(|text| {
+        serde_json::from_str(&text).map_err(|source| Error::Decode { source, body: text })
+    }))
+}
+
+fn details_from_row(row: &Row<'_>) -> rusqlite::Result<Result<DetailsQueryResponse>> {
+    let gdm = match geometry_column(row, 17)? {
+        Some(Ok(gdm)) => gdm,
+        Some(Err(e)) => return Ok(Err(e)),
+        None => {
+            return Err(rusqlite::Error::InvalidColumnType(
+                17,
+                "gdm".into(),
+                rusqlite::types::Type::Null,
+            ));
+        }
+    };
+    Ok(Ok(DetailsQueryResponse {
+        id: row.get(0)?,
+        standard_name: row.get(1)?,
+        roman_alphabet_spelling: row.get(2)?,
+        place_type: row.get(3)?,
+        place_type_code: row.get(4)?,
+        place_code: row.get(5)?,
+        province: row.get(6)?,
+        province_name: row.get(7)?,
+        city: row.get(8)?,
+        city_name: row.get(9)?,
+        area: row.get(10)?,
+        area_name: row.get(11)?,
+        old_name: row.get(12)?,
+        ethnic_minorities_writing: row.get(13)?,
+        place_meaning: row.get(14)?,
+        place_origin: row.get(15)?,
+        government_history: row.get(16)?,
+        gdm,
+    }))
+}
+
+fn record_from_row(row: &Row<'_>) -> rusqlite::Result<Result<Record>> {
+    let gdm = match geometry_column(row, 13)? {
+        Some(Ok(gdm)) => Some(gdm),
+        Some(Err(e)) => return Ok(Err(e)),
+        None => None,
+    };
+    Ok(Ok(Record {
+        id: row.get(0)?,
+        place_code: row.get(1)?,
+        standard_name: row.get(2)?,
+        roman_alphabet_spelling: row.get(3)?,
+        ethnic_minorities_writing: row.get(4)?,
+        place_type: row.get(5)?,
+        place_type_code: row.get(6)?,
+        province: row.get(7)?,
+        province_name: row.get(8)?,
+        city: row.get(9)?,
+        city_name: row.get(10)?,
+        area: row.get(11)?,
+        area_name: row.get(12)?,
+        gdm,
+        other: Default::default(),
+    }))
+}
+
+/// 当前毫秒时间戳
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_support::{sample_details, sample_details_with};
+    use crate::test_support::sample_record;
+
+    #[test]
+    fn test_upsert_and_query_details() {
+        let mut storage = Storage::open_in_memory().unwrap();
+        let run = storage.begin_run("测试").unwrap();
+        storage
+            .upsert_details(Some(run), &[sample_details()])
+            .unwrap();
+        let mut updated = sample_details();
+        updated.place_meaning = "更新后的含义".to_string();
+        storage
+            .upsert_details(
+                Some(run),
+                &[
+                    updated,
+                    sample_details_with("def", r#"{"type":"Point","coordinates":[1,2]}"#),
+                ],
+            )
+            .unwrap();
+        storage.finish_run(run, "finished").unwrap();
+
+        assert_eq!(storage.count_details().unwrap(), 2);
+        let details = storage.details("abc").unwrap().unwrap();
+        assert_eq!(details.place_meaning, "更新后的含义");
+        assert_eq!(details.gdm, sample_details().gdm);
+        assert!(storage.details("missing").unwrap().is_none());
+
+        let found = storage
+            .query_details(&ToponymQuery::new().name("唐庄").region("410122").limit(1))
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(
+            storage
+                .query_details(&ToponymQuery::new().region("410300000000"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            storage
+                .query_details(&ToponymQuery::new().name("%"))
+                .unwrap()
+                .is_empty()
+        );
+
+        let runs = storage.runs().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, "finished");
+        assert!(runs[0].finished_at.is_some());
+    }
+
+    #[test]
+    fn test_records_and_failures() {
+        let mut storage = Storage::open_in_memory().unwrap();
+        storage
+            .upsert_records(
+                None,
+                &[
+                    sample_record("abc", "唐庄村"),
+                    sample_record("xyz", "李庄村"),
+                ],
+            )
+            .unwrap();
+        storage
+            .upsert_records(None, &[sample_record("xyz", "李庄")])
+            .unwrap();
+        let records = storage
+            .query_records(&ToponymQuery::new().place_type_code("22200"))
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].standard_name, "李庄");
+        assert!(records[1].gdm.is_some());
+
+        let failure = Failure {
+            kind: FailureKind::Details,
+            target: "abc".to_string(),
+            name: Some("唐庄村".to_string()),
+            county_code: Some("410122".to_string()),
+            error: "超时".to_string(),
+            retry_count: 1,
+        };
+        storage.record_failure(None, &failure).unwrap();
+        storage
+            .record_failure(
+                None,
+                &Failure {
+                    kind: FailureKind::Search,
+                    target: "410122".to_string(),
+                    retry_count: 0,
+                    ..failure.clone()
+                },
+            )
+            .unwrap();
+        assert_eq!(storage.failures().unwrap().len(), 2);
+        assert_eq!(storage.missing_details().unwrap(), vec!["abc", "xyz"]);
+
+        // 获取详情成功后失败记录被清除
+        storage.upsert_details(None, &[sample_details()]).unwrap();
+        let failures = storage.failures().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, FailureKind::Search);
+        assert_eq!(storage.missing_details().unwrap(), vec!["xyz"]);
+    }
+}
@@ -0,0 +1,46 @@
+//! # 测试数据
+//!
+//! 多个模块的测试共用的搜索结果和行政区划树
+
+use crate::api::{DivisonQueryResult, Record};
+
+/// 测试用的搜索结果：中牟县的农村居民点，`gdm` 为点
+pub fn sample_record(id: &str, name: &str) -> Record {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "place_code": "410122000000",
+        "standard_name": name,
+        "roman_alphabet_spelling": "",
+        "place_type": "农村居民点",
+        "place_type_code": "22200",
+        "province_name": "河南省",
+        "city_name": "郑州市",
+        "area_name": "中牟县",
+        "area": "410122000000",
+        "city": "410100000000",
+        "province": "410000000000",
+        "gdm": {"type": "Point", "coordinates": [113.9, 34.7]}
+    }))
+    .unwrap()
+}
+
+/// 测试用的行政区划树：河南省下的郑州市、开封市和省直辖的济源市
+pub fn sample_division() -> DivisonQueryResult {
+    serde_json::from_value(serde_json::json!({
+        "code": "410000000000", "name": "河南省", "level": 1, "type": "省",
+        "children": [
+            {"code": "410100000000", "name": "郑州市", "level": 2, "type": "地级市", "children": [
+                {"code": "410102000000", "name": "中原区", "level": 3, "type": "市辖区", "children": []},
+                {"code": "410122000000", "name": "中牟县", "level": 3, "type": "县", "children": [
+                    {"code": "410122100000", "name": "城关镇", "level": 4, "type": "镇", "children": []}
+                ]},
+                {"code": "410181000000", "name": "巩义市", "level": 3, "type": "县级市", "children": []}
+            ]},
+            {"code": "410200000000", "name": "开封市", "level": 2, "type": "地级市", "children": [
+                {"code": "410225000000", "name": "兰考县", "level": 3, "type": "县", "children": []}
+            ]},
+            {"code": "419001000000", "name": "济源市", "level": 2, "type": "县级市", "children": []}
+        ]
+    }))
+    .unwrap()
+}
@@ -0,0 +1,44 @@
+//! # 北京时间日期
+//!
+//! 每日请求配额按北京时间零点重置，Shapefile 的 DBF 文件头也记录北京时间的日期，
+//! 两者共用这里的换算。
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 北京时间相对 UTC 的偏移（秒）
+const BEIJING_OFFSET: u64 = 8 * 3600;
+
+/// 当前北京时间的日序号（自 1970-01-01 起的天数）
+pub(crate) fn today() -> u64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    (secs + BEIJING_OFFSET) / 86400
+}
+
+/// 日序号对应的公历日期 `(年, 月, 日)`
+pub(crate) fn civil_date(day: u64) -> (i64, u8, u8) {
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(20_000), (2024, 10, 4));
+    }
+}